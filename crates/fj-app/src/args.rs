@@ -1,6 +1,7 @@
-use std::{path::PathBuf, str::FromStr as _};
+use std::{path::PathBuf, str::FromStr as _, time::Duration};
 
 use anyhow::anyhow;
+use fj_export::{Format, Unit};
 use fj_host::Parameters;
 use fj_kernel::algorithms::Tolerance;
 use fj_math::Scalar;
@@ -17,13 +18,85 @@ pub struct Args {
     #[clap(short, long)]
     pub export: Option<PathBuf>,
 
+    /// Format to export to, given as a file extension (e.g. `stl`, `glb`)
+    ///
+    /// Determined from the extension of the export path, if not specified.
+    #[clap(long)]
+    pub export_format: Option<Format>,
+
+    /// Unit of the model's coordinates, written to the exported file
+    ///
+    /// One of `mm`, `cm`, `m`, or `in`. Defaults to `mm`.
+    #[clap(long)]
+    pub export_unit: Option<Unit>,
+
+    /// Use the ASCII encoding, when exporting to STL or PLY
+    #[clap(long)]
+    pub export_ascii: bool,
+
+    /// Store the color of each triangle, when exporting to binary STL
+    ///
+    /// STL has no standard way to store colors. This uses the attribute bytes
+    /// of each triangle, which not all applications understand.
+    #[clap(long, conflicts_with = "export-ascii")]
+    pub export_stl_colors: bool,
+
+    /// Build the model using the `release` profile
+    #[clap(long, conflicts_with = "profile")]
+    pub release: bool,
+
+    /// Build the model using this Cargo profile
+    #[clap(long)]
+    pub profile: Option<String>,
+
+    /// Cargo features of the model to enable, separated by commas
+    #[clap(long, use_value_delimiter = true)]
+    pub features: Option<Vec<String>>,
+
+    /// The directory that the model is compiled to
+    #[clap(long)]
+    pub target_dir: Option<PathBuf>,
+
+    /// Evaluate the model in a separate process
+    ///
+    /// Protects the application from models that crash or never return, at
+    /// the cost of some overhead.
+    #[clap(long)]
+    pub isolate_model: bool,
+
+    /// Stop evaluating the model after this many seconds
+    ///
+    /// Only applies, if the model is evaluated in a separate process.
+    #[clap(
+        long,
+        requires = "isolate-model",
+        parse(try_from_str = parse_duration)
+    )]
+    pub model_timeout: Option<Duration>,
+
     /// Parameters for the model, each in the form `key=value`
     #[clap(short, long, parse(try_from_str = parse_parameters))]
     pub parameters: Option<Parameters>,
 
+    /// Print the parameters that the model accepts, then exit
+    #[clap(long)]
+    pub list_parameters: bool,
+
     /// Model deviation tolerance
     #[clap[short, long, parse(try_from_str = parse_tolerance)]]
     pub tolerance: Option<Tolerance>,
+
+    /// Model deviation tolerance, relative to the size of each feature
+    #[clap(long, parse(try_from_str = parse_scalar))]
+    pub relative_tolerance: Option<Scalar>,
+
+    /// Maximum angle between the model and its approximation, in degrees
+    #[clap(long, parse(try_from_str = parse_angle))]
+    pub angular_tolerance: Option<Scalar>,
+
+    /// Record triangulation checks, to display them in the debug view
+    #[clap(long)]
+    pub debug_triangulation: bool,
 }
 
 impl Args {
@@ -59,6 +132,15 @@ fn parse_parameters(input: &str) -> anyhow::Result<Parameters> {
     Ok(parameters)
 }
 
+fn parse_duration(input: &str) -> anyhow::Result<Duration> {
+    let seconds = f64::from_str(input)?;
+    if !(0. ..=f64::from(u32::MAX)).contains(&seconds) {
+        return Err(anyhow!("Invalid number of seconds: {seconds}"));
+    }
+
+    Ok(Duration::from_secs_f64(seconds))
+}
+
 fn parse_tolerance(input: &str) -> anyhow::Result<Tolerance> {
     let tolerance = f64::from_str(input)?;
     let tolerance = Scalar::from_f64(tolerance);
@@ -66,3 +148,13 @@ fn parse_tolerance(input: &str) -> anyhow::Result<Tolerance> {
 
     Ok(tolerance)
 }
+
+fn parse_scalar(input: &str) -> anyhow::Result<Scalar> {
+    let scalar = f64::from_str(input)?;
+    Ok(Scalar::from_f64(scalar))
+}
+
+fn parse_angle(input: &str) -> anyhow::Result<Scalar> {
+    let degrees = f64::from_str(input)?;
+    Ok(Scalar::from_f64(degrees.to_radians()))
+}
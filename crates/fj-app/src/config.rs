@@ -11,6 +11,10 @@ use serde::Deserialize;
 pub struct Config {
     pub default_path: Option<PathBuf>,
     pub default_model: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub features: Option<Vec<String>>,
+    pub target_dir: Option<PathBuf>,
+    pub ignored_paths: Option<Vec<String>>,
 }
 
 impl Config {
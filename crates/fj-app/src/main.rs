@@ -15,12 +15,12 @@
 mod args;
 mod config;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Context as _};
-use fj_export::export;
-use fj_host::{Model, Parameters};
-use fj_operations::shape_processor::ShapeProcessor;
+use fj_export::{ExportOptions, Format, PlyEncoding, StlEncoding};
+use fj_host::{BuildOptions, Evaluation, Model, Parameters};
+use fj_operations::{cache::Cache, shape_processor::ShapeProcessor};
 use fj_window::run::run;
 use tracing_subscriber::fmt::format;
 use tracing_subscriber::EnvFilter;
@@ -28,6 +28,10 @@ use tracing_subscriber::EnvFilter;
 use crate::{args::Args, config::Config};
 
 fn main() -> anyhow::Result<()> {
+    // If this is a child process that was started to evaluate the model, this
+    // won't return.
+    fj_host::evaluate_if_child_process();
+
     // Respect `RUST_LOG`. If that's not defined or erroneous, log warnings and
     // above.
     //
@@ -53,19 +57,88 @@ fn main() -> anyhow::Result<()> {
     })?;
     path.push(model);
 
-    let model = Model::from_path(path.clone())
-        .with_context(|| format!("Failed to load model: {}", path.display()))?;
+    let profile = if args.release {
+        Some(String::from("release"))
+    } else {
+        args.profile.or(config.profile)
+    };
+    let build_options = BuildOptions {
+        profile,
+        features: args.features.or(config.features).unwrap_or_default(),
+        target_dir: args.target_dir.or(config.target_dir),
+    };
+
+    let name = model_name(&path);
+    let mut model = Model::from_path(path.clone())
+        .with_context(|| format!("Failed to load model: {}", path.display()))?
+        .with_build_options(build_options);
+    if let Some(ignored_paths) = config.ignored_paths {
+        model = model.with_ignored_paths(ignored_paths);
+    }
+    if args.isolate_model {
+        model = model.with_evaluation(Evaluation::ChildProcess {
+            timeout: args.model_timeout,
+        });
+    }
     let parameters = args.parameters.unwrap_or_else(Parameters::empty);
 
+    if args.list_parameters {
+        let metadata = model.load_metadata().map_err(report_compile_errors)?;
+        print_metadata(&metadata);
+
+        return Ok(());
+    }
+
     let shape_processor = ShapeProcessor {
         tolerance: args.tolerance,
+        relative_tolerance: args.relative_tolerance,
+        angular_tolerance: args.angular_tolerance,
+        record_triangle_edge_checks: args.debug_triangulation,
+        cache: Cache::new(),
     };
 
     if let Some(path) = args.export {
-        let shape = model.load_once(&parameters)?;
-        let shape = shape_processor.process(&shape)?;
+        let format = match args.export_format {
+            Some(format) => format,
+            None => Format::from_path(&path)?,
+        };
+        let format = match format {
+            Format::Stl(_) if args.export_ascii => {
+                Format::Stl(StlEncoding::Ascii)
+            }
+            Format::Stl(_) if args.export_stl_colors => {
+                Format::Stl(StlEncoding::BinaryWithColor)
+            }
+            Format::Ply(_) if args.export_ascii => {
+                Format::Ply(PlyEncoding::Ascii)
+            }
+            format => format,
+        };
+
+        let options = ExportOptions {
+            format: Some(format),
+            unit: args.export_unit.unwrap_or_default(),
+            name,
+        };
+
+        let shape = model
+            .load_once(&parameters)
+            .map_err(report_compile_errors)?;
+
+        if format.requires_brep() {
+            let faces = shape_processor.process_brep(&shape)?;
+            options.export_brep(&faces, &path)?;
 
-        export(&shape.mesh, &path)?;
+            return Ok(());
+        }
+
+        let parts = shape_processor
+            .process_parts(&shape)?
+            .into_iter()
+            .map(|part| part.mesh)
+            .collect::<Vec<_>>();
+
+        options.export_parts(&parts, &path)?;
 
         return Ok(());
     }
@@ -75,3 +148,71 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Determine the name of the model from the path to its crate
+///
+/// This is the name of the crate's directory, which is usually what the user
+/// thinks of as the model's name. The path might be relative (like `.`), so it
+/// is resolved first.
+fn model_name(path: &Path) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    let name = path.file_name()?;
+
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Print the errors reported by the compiler, if the model failed to compile
+fn report_compile_errors(err: fj_host::Error) -> anyhow::Error {
+    if let fj_host::Error::Compile { diagnostics } = &err {
+        for diagnostic in diagnostics {
+            eprintln!("error: {}", diagnostic);
+        }
+    }
+
+    err.into()
+}
+
+fn print_metadata(metadata: &fj::ModelMetadata) {
+    println!("{}", metadata.name);
+    if let Some(description) = &metadata.description {
+        println!("{}", indent(description, 4));
+    }
+
+    if metadata.parameters.is_empty() {
+        println!();
+        println!("The model has no parameters.");
+        return;
+    }
+
+    println!();
+    println!("Parameters:");
+    for parameter in &metadata.parameters {
+        let constraints = [
+            ("default", &parameter.default),
+            ("min", &parameter.min),
+            ("max", &parameter.max),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| {
+            value.as_ref().map(|value| format!("{}: {}", name, value))
+        })
+        .collect::<Vec<_>>();
+
+        print!("    {}: {}", parameter.name, parameter.ty);
+        if !constraints.is_empty() {
+            print!(" ({})", constraints.join(", "));
+        }
+        println!();
+
+        if let Some(description) = &parameter.description {
+            println!("{}", indent(description, 8));
+        }
+    }
+}
+
+fn indent(text: &str, width: usize) -> String {
+    text.lines()
+        .map(|line| format!("{:width$}{}", "", line, width = width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
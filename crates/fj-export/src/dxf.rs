@@ -0,0 +1,233 @@
+//! Export of 2D faces to DXF
+//!
+//! The file uses the R12 structure, which is the most widely supported one.
+//! Circles are written as `CIRCLE` entities, all other cycles as closed
+//! `POLYLINE`s, with arcs encoded as bulges.
+//!
+//! R12 supports neither true colors, nor a declaration of the unit. Each
+//! face's entities are placed on a layer that is named after the face's color,
+//! and gets the closest of the basic indexed colors. The unit is only noted in
+//! a comment.
+
+use std::{
+    collections::BTreeSet,
+    io::{self, Write},
+};
+
+use fj_interop::mesh::Color;
+use fj_kernel::objects::Face;
+
+use crate::{
+    outline::{self, Outline},
+    Error, Unit,
+};
+
+pub fn write(
+    faces: &[Face],
+    unit: Unit,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let outlines = outline::outlines(faces)?;
+    write_outlines(&outlines, unit, out)?;
+
+    Ok(())
+}
+
+fn write_outlines(
+    outlines: &[Outline],
+    unit: Unit,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let mut dxf = Dxf { out };
+
+    let unit = match unit {
+        Unit::Millimeter => "millimeters",
+        Unit::Centimeter => "centimeters",
+        Unit::Meter => "meters",
+        Unit::Inch => "inches",
+    };
+
+    dxf.pair(999, format_args!("Unit: {unit}"))?;
+
+    dxf.pair(0, "SECTION")?;
+    dxf.pair(2, "HEADER")?;
+    dxf.pair(9, "$ACADVER")?;
+    dxf.pair(1, "AC1009")?;
+    dxf.pair(0, "ENDSEC")?;
+
+    let colors = outlines
+        .iter()
+        .map(|outline| outline.color)
+        .collect::<BTreeSet<_>>();
+
+    dxf.pair(0, "SECTION")?;
+    dxf.pair(2, "TABLES")?;
+
+    dxf.pair(0, "TABLE")?;
+    dxf.pair(2, "LTYPE")?;
+    dxf.pair(70, 1)?;
+    dxf.pair(0, "LTYPE")?;
+    dxf.pair(2, "CONTINUOUS")?;
+    dxf.pair(70, 0)?;
+    dxf.pair(3, "Solid line")?;
+    dxf.pair(72, 65)?;
+    dxf.pair(73, 0)?;
+    dxf.pair(40, 0.)?;
+    dxf.pair(0, "ENDTAB")?;
+
+    dxf.pair(0, "TABLE")?;
+    dxf.pair(2, "LAYER")?;
+    dxf.pair(70, colors.len())?;
+    for &color in &colors {
+        dxf.pair(0, "LAYER")?;
+        dxf.pair(2, layer(color))?;
+        dxf.pair(70, 0)?;
+        dxf.pair(62, color_index(color))?;
+        dxf.pair(6, "CONTINUOUS")?;
+    }
+    dxf.pair(0, "ENDTAB")?;
+
+    dxf.pair(0, "ENDSEC")?;
+
+    dxf.pair(0, "SECTION")?;
+    dxf.pair(2, "ENTITIES")?;
+
+    for outline in outlines {
+        // The entities get their color from the layer.
+        let layer = layer(outline.color);
+
+        for path in &outline.paths {
+            match path {
+                outline::Path::Circle {
+                    center: [x, y],
+                    radius,
+                } => {
+                    dxf.pair(0, "CIRCLE")?;
+                    dxf.pair(8, &layer)?;
+                    dxf.point([*x, *y])?;
+                    dxf.pair(40, radius)?;
+                }
+                outline::Path::Segments(segments) => {
+                    dxf.pair(0, "POLYLINE")?;
+                    dxf.pair(8, &layer)?;
+                    dxf.pair(66, 1)?; // vertices follow
+                    dxf.point([0., 0.])?;
+                    dxf.pair(70, 1)?; // closed
+
+                    for segment in segments {
+                        dxf.pair(0, "VERTEX")?;
+                        dxf.pair(8, &layer)?;
+                        dxf.point(segment.start)?;
+
+                        // The bulge of a vertex defines the arc from that
+                        // vertex to the next one. It's the tangent of a
+                        // quarter of the arc's angle.
+                        if let Some(arc) = &segment.arc {
+                            dxf.pair(42, (arc.angle / 4.).tan())?;
+                        }
+                    }
+
+                    dxf.pair(0, "SEQEND")?;
+                    dxf.pair(8, &layer)?;
+                }
+            }
+        }
+    }
+
+    dxf.pair(0, "ENDSEC")?;
+    dxf.pair(0, "EOF")?;
+
+    Ok(())
+}
+
+/// The name of the layer for faces of the given color
+fn layer(Color([r, g, b, a]): Color) -> String {
+    format!("color_{r:02x}{g:02x}{b:02x}{a:02x}")
+}
+
+/// Find the closest of the basic colors of the AutoCAD Color Index
+///
+/// Most of the indexed colors are variations of the basic ones, which makes
+/// them no better a match for arbitrary colors. The layer names preserve the
+/// exact color.
+fn color_index(Color([r, g, b, _]): Color) -> u8 {
+    const BASIC_COLORS: [(u8, [u8; 3]); 9] = [
+        (1, [255, 0, 0]),
+        (2, [255, 255, 0]),
+        (3, [0, 255, 0]),
+        (4, [0, 255, 255]),
+        (5, [0, 0, 255]),
+        (6, [255, 0, 255]),
+        (7, [255, 255, 255]),
+        (8, [128, 128, 128]),
+        (9, [192, 192, 192]),
+    ];
+
+    let distance = |color: [u8; 3]| {
+        [r, g, b]
+            .into_iter()
+            .zip(color)
+            .map(|(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+            .sum::<i32>()
+    };
+
+    BASIC_COLORS
+        .into_iter()
+        .min_by_key(|&(_, color)| distance(color))
+        .map(|(index, _)| index)
+        .expect("List of basic colors is not empty")
+}
+
+struct Dxf<'r, W> {
+    out: &'r mut W,
+}
+
+impl<W: Write> Dxf<'_, W> {
+    /// Write a group code and its value
+    fn pair(
+        &mut self,
+        code: u16,
+        value: impl std::fmt::Display,
+    ) -> io::Result<()> {
+        writeln!(self.out, "{code:>3}")?;
+        writeln!(self.out, "{value}")
+    }
+
+    fn point(&mut self, [x, y]: [f64; 2]) -> io::Result<()> {
+        self.pair(10, x)?;
+        self.pair(20, y)?;
+        self.pair(30, 0.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{outline::tests::faces, Unit};
+
+    #[test]
+    fn write() -> anyhow::Result<()> {
+        let mut dxf = Vec::new();
+        super::write(&faces(), Unit::Millimeter, &mut dxf)?;
+        let dxf = String::from_utf8(dxf)?;
+
+        let count =
+            |entity: &str| dxf.matches(&format!("  0\n{entity}\n")).count();
+
+        // The square and its hole are polylines, the circle a circle.
+        assert_eq!(count("POLYLINE"), 2);
+        assert_eq!(count("VERTEX"), 8);
+        assert_eq!(count("SEQEND"), 2);
+        assert_eq!(count("CIRCLE"), 1);
+
+        // R12 knows neither true colors, nor units.
+        assert!(!dxf.contains("420\n"));
+        assert!(!dxf.contains("$INSUNITS"));
+
+        // The blue face is on its own layer, which is blue too.
+        assert!(dxf.contains("  2\ncolor_0000ff80\n 70\n0\n 62\n5\n"));
+        assert!(dxf.contains("  8\ncolor_0000ff80\n"));
+        assert!(dxf.ends_with("  0\nEOF\n"));
+
+        Ok(())
+    }
+}
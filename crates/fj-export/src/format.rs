@@ -0,0 +1,168 @@
+//! The file formats and units of exported models
+
+use std::{path::Path, str::FromStr};
+
+use crate::{Error, PlyEncoding, StlEncoding};
+
+/// A file format that models can be exported to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// 3MF (3D Manufacturing Format)
+    ThreeMf,
+
+    /// glTF 2.0, either as JSON (`.gltf`) or binary (`.glb`)
+    Gltf {
+        /// Whether to use the binary container format
+        binary: bool,
+    },
+
+    /// Wavefront OBJ
+    Obj,
+
+    /// PLY (Polygon File Format)
+    Ply(PlyEncoding),
+
+    /// STL
+    Stl(StlEncoding),
+
+    /// STEP (ISO 10303-21)
+    Step,
+
+    /// SVG, for 2D models
+    Svg,
+
+    /// DXF, for 2D models
+    Dxf,
+}
+
+impl Format {
+    /// Determine the format from a file extension
+    ///
+    /// The extension is matched case-insensitively. Returns `None`, if the
+    /// extension is not recognized.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let format = match extension.to_ascii_uppercase().as_str() {
+            "3MF" => Self::ThreeMf,
+            "GLTF" => Self::Gltf { binary: false },
+            "GLB" => Self::Gltf { binary: true },
+            "OBJ" => Self::Obj,
+            "PLY" => Self::Ply(PlyEncoding::BinaryLittleEndian),
+            "STL" => Self::Stl(StlEncoding::Binary),
+            "STLA" => Self::Stl(StlEncoding::Ascii),
+            "STEP" | "STP" => Self::Step,
+            "SVG" => Self::Svg,
+            "DXF" => Self::Dxf,
+            _ => return None,
+        };
+
+        Some(format)
+    }
+
+    /// Determine the format from the extension of the given path
+    pub fn from_path(path: &Path) -> Result<Self, Error> {
+        let extension = path.extension().ok_or(Error::NoExtension)?;
+        let extension = extension.to_string_lossy();
+
+        Self::from_extension(&extension)
+            .ok_or_else(|| Error::InvalidExtension(extension.into_owned()))
+    }
+
+    /// Indicate whether this format requires the boundary representation of
+    /// the model
+    ///
+    /// All other formats are exported from a triangle mesh.
+    pub fn requires_brep(&self) -> bool {
+        matches!(self, Self::Step | Self::Svg | Self::Dxf)
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_extension(s)
+            .ok_or_else(|| Error::InvalidExtension(s.to_owned()))
+    }
+}
+
+/// The unit that the coordinates of a model are given in
+///
+/// Fornjot models have no inherent unit. The unit is written to the exported
+/// file, for the formats that support this. Coordinates are never scaled.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Unit {
+    /// Millimeters
+    Millimeter,
+
+    /// Centimeters
+    Centimeter,
+
+    /// Meters
+    Meter,
+
+    /// Inches
+    Inch,
+}
+
+impl Unit {
+    /// The length of this unit, in meters
+    pub fn in_meters(&self) -> f64 {
+        match self {
+            Self::Millimeter => 0.001,
+            Self::Centimeter => 0.01,
+            Self::Meter => 1.,
+            Self::Inch => 0.0254,
+        }
+    }
+}
+
+impl Default for Unit {
+    fn default() -> Self {
+        Self::Millimeter
+    }
+}
+
+impl FromStr for Unit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mm" => Ok(Self::Millimeter),
+            "cm" => Ok(Self::Centimeter),
+            "m" => Ok(Self::Meter),
+            "in" | "inch" => Ok(Self::Inch),
+            _ => Err(Error::InvalidUnit(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{PlyEncoding, StlEncoding};
+
+    use super::{Format, Unit};
+
+    #[test]
+    fn from_path() {
+        let format = |path: &str| Format::from_path(Path::new(path)).ok();
+
+        assert_eq!(format("model.GLB"), Some(Format::Gltf { binary: true }));
+        assert_eq!(format("model.stla"), Some(Format::Stl(StlEncoding::Ascii)));
+        assert_eq!(
+            format("model.ply"),
+            Some(Format::Ply(PlyEncoding::BinaryLittleEndian))
+        );
+        assert_eq!(format("model.stp"), Some(Format::Step));
+        assert_eq!(format("model.xyz"), None);
+        assert_eq!(format("model"), None);
+    }
+
+    #[test]
+    fn parse_unit() {
+        assert_eq!("in".parse::<Unit>().ok(), Some(Unit::Inch));
+        assert_eq!("cm".parse::<Unit>().ok(), Some(Unit::Centimeter));
+        assert!("furlong".parse::<Unit>().is_err());
+    }
+}
@@ -0,0 +1,414 @@
+//! Export to glTF 2.0, either as JSON (`.gltf`) or binary (`.glb`)
+//!
+//! Each part becomes a separate node, with one primitive per color. Each color
+//! becomes a material.
+//!
+//! Vertices are shared between the triangles of a primitive, as long as they
+//! have the same normal. Fornjot models are made of flat triangles, so vertices
+//! at sharp edges need to be duplicated to get correct shading.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+};
+
+use fj_interop::mesh::{Color, Mesh};
+use fj_math::{Point, Vector};
+use serde_json::json;
+
+use crate::{Error, Unit};
+
+pub fn write(
+    parts: &[Mesh<Point<3>>],
+    unit: Unit,
+    binary: bool,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    if binary {
+        write_glb(parts, unit, out)
+    } else {
+        write_gltf(parts, unit, out)
+    }
+}
+
+fn write_gltf(
+    parts: &[Mesh<Point<3>>],
+    unit: Unit,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let (mut document, buffer) = build(parts, unit);
+
+    // A buffer must not be empty. Without any triangles, there's no buffer.
+    if !buffer.is_empty() {
+        document["buffers"] = json!([{
+            "byteLength": buffer.len(),
+            "uri": format!(
+                "data:application/octet-stream;base64,{}",
+                base64::encode(&buffer),
+            ),
+        }]);
+    }
+
+    serde_json::to_writer_pretty(out, &document)?;
+
+    Ok(())
+}
+
+fn write_glb(
+    parts: &[Mesh<Point<3>>],
+    unit: Unit,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let (mut document, mut buffer) = build(parts, unit);
+
+    // A buffer must not be empty. Without any triangles, there's neither a
+    // buffer, nor a binary chunk.
+    if !buffer.is_empty() {
+        document["buffers"] = json!([{
+            "byteLength": buffer.len(),
+        }]);
+    }
+
+    // Chunks need to be aligned to 4 bytes. JSON is padded with spaces, binary
+    // data with zeros.
+    let mut json = serde_json::to_vec(&document)?;
+    pad(&mut json, b' ');
+    pad(&mut buffer, 0);
+
+    let mut chunks = vec![(b"JSON", json)];
+    if !buffer.is_empty() {
+        chunks.push((b"BIN\0", buffer));
+    }
+
+    let length = 12
+        + chunks
+            .iter()
+            .map(|(_, chunk)| 8 + chunk.len())
+            .sum::<usize>();
+    let length = u32::try_from(length).map_err(|_| Error::InvalidSize)?;
+
+    out.write_all(b"glTF")?;
+    out.write_all(&2u32.to_le_bytes())?;
+    out.write_all(&length.to_le_bytes())?;
+
+    for (chunk_type, chunk) in chunks {
+        out.write_all(&(chunk.len() as u32).to_le_bytes())?;
+        out.write_all(chunk_type)?;
+        out.write_all(&chunk)?;
+    }
+
+    Ok(())
+}
+
+/// Build the glTF document and its binary buffer
+///
+/// The document's `buffers` field is left for the caller to fill in, as that
+/// depends on whether the buffer is embedded or stored in a GLB chunk.
+///
+/// Parts without triangles are skipped, and empty arrays are left out of the
+/// document, as glTF doesn't allow either.
+fn build(parts: &[Mesh<Point<3>>], unit: Unit) -> (serde_json::Value, Vec<u8>) {
+    let mut buffer = Vec::new();
+    let mut buffer_views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut materials = Vec::new();
+    let mut material_indices = HashMap::new();
+    let mut meshes = Vec::new();
+    let mut nodes = Vec::new();
+
+    for (i, part) in parts.iter().enumerate() {
+        if part.triangles().next().is_none() {
+            continue;
+        }
+
+        let mut primitives = Vec::new();
+
+        for (color, primitive) in primitives_by_color(part) {
+            let material_index =
+                *material_indices.entry(color).or_insert_with(|| {
+                    materials.push(material(color));
+                    materials.len() - 1
+                });
+
+            let mut push_accessor =
+                |data: &[u8], accessor: serde_json::Value| {
+                    buffer_views.push(json!({
+                        "buffer": 0,
+                        "byteOffset": buffer.len(),
+                        "byteLength": data.len(),
+                    }));
+                    buffer.extend_from_slice(data);
+
+                    let mut accessor = accessor;
+                    accessor["bufferView"] = json!(buffer_views.len() - 1);
+                    accessors.push(accessor);
+
+                    accessors.len() - 1
+                };
+
+            let [min, max] = primitive.bounds();
+
+            let positions = push_accessor(
+                &f32_bytes(&primitive.positions),
+                json!({
+                    "componentType": FLOAT,
+                    "count": primitive.positions.len(),
+                    "type": "VEC3",
+                    "min": min,
+                    "max": max,
+                }),
+            );
+            let normals = push_accessor(
+                &f32_bytes(&primitive.normals),
+                json!({
+                    "componentType": FLOAT,
+                    "count": primitive.normals.len(),
+                    "type": "VEC3",
+                }),
+            );
+            let indices = push_accessor(
+                &primitive
+                    .indices
+                    .iter()
+                    .flat_map(|index| index.to_le_bytes())
+                    .collect::<Vec<_>>(),
+                json!({
+                    "componentType": UNSIGNED_INT,
+                    "count": primitive.indices.len(),
+                    "type": "SCALAR",
+                }),
+            );
+
+            primitives.push(json!({
+                "attributes": {
+                    "POSITION": positions,
+                    "NORMAL": normals,
+                },
+                "indices": indices,
+                "material": material_index,
+            }));
+        }
+
+        meshes.push(json!({ "primitives": primitives }));
+        nodes.push(json!({
+            "name": format!("part-{i}"),
+            "mesh": meshes.len() - 1,
+        }));
+    }
+
+    // glTF uses a Y-up coordinate system, while Fornjot uses Z-up. Rotate the
+    // whole model by -90° around the x-axis, to convert between the two.
+    //
+    // glTF also uses meters as its unit. Scale the whole model accordingly.
+    let half_sqrt_2 = std::f64::consts::FRAC_1_SQRT_2;
+    let scale = unit.in_meters();
+    let mut root = json!({
+        "name": "model",
+        "rotation": [-half_sqrt_2, 0., 0., half_sqrt_2],
+        "scale": [scale, scale, scale],
+    });
+    insert_non_empty(
+        &mut root,
+        "children",
+        (0..nodes.len()).map(|i| json!(i)).collect(),
+    );
+    nodes.push(root);
+
+    let mut document = json!({
+        "asset": {
+            "version": "2.0",
+            "generator": "Fornjot",
+        },
+        "scene": 0,
+        "scenes": [{ "nodes": [nodes.len() - 1] }],
+        "nodes": nodes,
+    });
+    insert_non_empty(&mut document, "meshes", meshes);
+    insert_non_empty(&mut document, "materials", materials);
+    insert_non_empty(&mut document, "accessors", accessors);
+    insert_non_empty(&mut document, "bufferViews", buffer_views);
+
+    (document, buffer)
+}
+
+/// Insert an array into a JSON object, unless it is empty
+fn insert_non_empty(
+    object: &mut serde_json::Value,
+    key: &str,
+    values: Vec<serde_json::Value>,
+) {
+    if !values.is_empty() {
+        object[key] = serde_json::Value::Array(values);
+    }
+}
+
+#[derive(Default)]
+struct Primitive {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+}
+
+impl Primitive {
+    fn bounds(&self) -> [[f32; 3]; 2] {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+
+        for position in &self.positions {
+            for ((min, max), &value) in
+                min.iter_mut().zip(max.iter_mut()).zip(position)
+            {
+                *min = min.min(value);
+                *max = max.max(value);
+            }
+        }
+
+        [min, max]
+    }
+}
+
+fn primitives_by_color(mesh: &Mesh<Point<3>>) -> BTreeMap<Color, Primitive> {
+    let mut primitives: BTreeMap<Color, Primitive> = BTreeMap::new();
+    let mut indices_by_vertex = HashMap::new();
+
+    for triangle in mesh.triangles() {
+        let primitive = primitives.entry(triangle.color).or_default();
+        let normal = triangle.inner.normal();
+
+        for point in triangle.inner.points() {
+            let key = (triangle.color, point, normal);
+            let index = *indices_by_vertex.entry(key).or_insert_with(|| {
+                primitive.positions.push(to_f32(point.coords));
+                primitive.normals.push(to_f32(normal));
+                primitive.positions.len() as u32 - 1
+            });
+
+            primitive.indices.push(index);
+        }
+    }
+
+    primitives
+}
+
+fn material(Color([r, g, b, a]): Color) -> serde_json::Value {
+    // The base color factor is defined in linear color space, while our colors
+    // are sRGB.
+    let [r, g, b] = [r, g, b].map(srgb_to_linear);
+    let a = f64::from(a) / 255.;
+
+    let mut material = json!({
+        "pbrMetallicRoughness": {
+            "baseColorFactor": [r, g, b, a],
+            "metallicFactor": 0.,
+            "roughnessFactor": 0.5,
+        },
+    });
+    if a < 1. {
+        material["alphaMode"] = json!("BLEND");
+    }
+
+    material
+}
+
+fn srgb_to_linear(value: u8) -> f64 {
+    let value = f64::from(value) / 255.;
+
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn to_f32(vector: Vector<3>) -> [f32; 3] {
+    vector.components.map(|s| s.into_f32())
+}
+
+fn f32_bytes(values: &[[f32; 3]]) -> Vec<u8> {
+    values
+        .iter()
+        .flatten()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn pad(data: &mut Vec<u8>, with: u8) {
+    while data.len() % 4 != 0 {
+        data.push(with);
+    }
+}
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+
+#[cfg(test)]
+mod tests {
+    use fj_interop::mesh::{Color, Mesh};
+    use fj_math::Point;
+
+    use crate::Unit;
+
+    #[test]
+    fn build() {
+        let [a, b, c, d, e] = [
+            [0., 0., 0.],
+            [1., 0., 0.],
+            [1., 1., 0.],
+            [0., 1., 0.],
+            [0., 0., 1.],
+        ]
+        .map(Point::from);
+
+        let mut first = Mesh::new();
+        first.push_triangle([a, b, c], Color([255, 0, 0, 255]));
+        first.push_triangle([a, c, d], Color([255, 0, 0, 255]));
+
+        let mut second = Mesh::new();
+        second.push_triangle([a, b, e], Color([0, 255, 0, 200]));
+
+        let (document, buffer) =
+            super::build(&[first, second], Unit::Millimeter);
+
+        // One node per part, plus the root node.
+        assert_eq!(document["nodes"].as_array().unwrap().len(), 3);
+        assert_eq!(document["nodes"][2]["scale"][0], 0.001);
+        assert_eq!(document["materials"].as_array().unwrap().len(), 2);
+        assert_eq!(document["materials"][1]["alphaMode"], "BLEND");
+
+        // The triangles of the first part share a normal, so they share
+        // vertices too.
+        let first_primitive = &document["meshes"][0]["primitives"][0];
+        let positions = first_primitive["attributes"]["POSITION"]
+            .as_u64()
+            .unwrap() as usize;
+        assert_eq!(document["accessors"][positions]["count"], 4);
+
+        // 4 + 3 vertices with position and normal, 6 + 3 indices
+        assert_eq!(buffer.len(), (4 + 3) * 2 * 12 + (6 + 3) * 4);
+    }
+
+    #[test]
+    fn build_skips_empty_parts() {
+        let [a, b, c] =
+            [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.]].map(Point::from);
+
+        let mut part = Mesh::new();
+        part.push_triangle([a, b, c], Color([255, 0, 0, 255]));
+
+        let (document, _) =
+            super::build(&[Mesh::new(), part], Unit::Millimeter);
+
+        // One node for the non-empty part, plus the root node.
+        assert_eq!(document["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(document["nodes"][1]["children"], serde_json::json!([0]));
+        assert_eq!(document["meshes"].as_array().unwrap().len(), 1);
+
+        let (document, buffer) = super::build(&[Mesh::new()], Unit::Meter);
+
+        assert!(buffer.is_empty());
+        assert!(document["nodes"][0].get("children").is_none());
+        for key in ["meshes", "materials", "accessors", "bufferViews"] {
+            assert!(document.get(key).is_none(), "{key} should be omitted");
+        }
+    }
+}
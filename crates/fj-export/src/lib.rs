@@ -14,102 +14,153 @@
 
 #![warn(missing_docs)]
 
-use std::{fs::File, path::Path};
+mod dxf;
+mod format;
+mod gltf;
+mod obj;
+mod options;
+mod outline;
+mod ply;
+mod step;
+mod stl;
+mod svg;
+mod three_mf;
+
+pub use self::{
+    format::{Format, Unit},
+    options::ExportOptions,
+    ply::PlyEncoding,
+    stl::StlEncoding,
+};
+
+use std::{io::Write, path::Path, slice};
 
 use thiserror::Error;
 
 use fj_interop::mesh::Mesh;
-use fj_math::{Point, Triangle};
+use fj_kernel::objects::Face;
+use fj_math::Point;
 
 /// Export the provided mesh to the file at the given path.
 ///
 /// This function will create a file if it does not exist, and will truncate it if it does.
 ///
-/// Currently 3MF & STL file types are supported. The case insensitive file extension of
-/// the provided path is used to switch between supported types.
+/// Currently 3MF, STL, OBJ, PLY & glTF (`.gltf`/`.glb`) file types are supported. The case
+/// insensitive file extension of the provided path is used to switch between supported types.
+///
+/// OBJ export writes an additional material library next to the exported file,
+/// with the same name and the extension `.mtl`. PLY export uses the binary
+/// encoding; use [`export_ply`] to choose the encoding.
+///
+/// STL export uses the binary encoding for the `.stl` extension, and the ASCII
+/// encoding for the `.stla` extension. Use [`export_stl`] to include colors.
+///
+/// STEP, SVG & DXF files can't be exported from a mesh. Use [`export_brep`]
+/// for those.
+///
+/// This is a shortcut for [`ExportOptions::export_parts`] with the default
+/// options. Use [`ExportOptions`] for more control.
 pub fn export(mesh: &Mesh<Point<3>>, path: &Path) -> Result<(), Error> {
-    match path.extension() {
-        Some(extension) if extension.to_ascii_uppercase() == "3MF" => {
-            export_3mf(mesh, path)
-        }
-        Some(extension) if extension.to_ascii_uppercase() == "STL" => {
-            export_stl(mesh, path)
-        }
-        Some(extension) => Err(Error::InvalidExtension(
-            extension.to_string_lossy().into_owned(),
-        )),
-        None => Err(Error::NoExtension),
-    }
+    export_parts(slice::from_ref(mesh), path)
 }
 
-fn export_3mf(mesh: &Mesh<Point<3>>, path: &Path) -> Result<(), Error> {
-    let vertices = mesh.vertices().map(|vertex| vertex.into()).collect();
-
-    let indices: Vec<_> = mesh.indices().collect();
-    let triangles = indices
-        .chunks(3)
-        .map(|triangle| {
-            [
-                triangle[0] as usize,
-                triangle[1] as usize,
-                triangle[2] as usize,
-            ]
-        })
-        .collect();
-
-    let mesh = threemf::TriangleMesh {
-        vertices,
-        triangles,
+/// Export a model that consists of multiple parts to the given path
+///
+/// Works like [`export`], but keeps the parts separate, for file types that
+/// support this. Currently, those are glTF, which exports each part as a
+/// separate node, and 3MF, which exports each part as a separate object. For
+/// all other file types, the parts are merged into a single mesh.
+pub fn export_parts(
+    parts: &[Mesh<Point<3>>],
+    path: &Path,
+) -> Result<(), Error> {
+    ExportOptions::default().export_parts(parts, path)
+}
+
+/// Export the provided mesh to the given writer, using the given format
+///
+/// Like [`export`], but doesn't require a file. OBJ export doesn't write a
+/// material library in this case.
+pub fn export_to_writer(
+    mesh: &Mesh<Point<3>>,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let options = ExportOptions {
+        format: Some(format),
+        ..ExportOptions::default()
     };
 
-    threemf::write(path, &mesh)?;
+    options.write_parts(slice::from_ref(mesh), out)
+}
 
-    Ok(())
+/// Export the provided mesh to a PLY file at the given path
+///
+/// Like [`export`], but allows for choosing the encoding of the PLY file.
+pub fn export_ply(
+    mesh: &Mesh<Point<3>>,
+    path: &Path,
+    encoding: PlyEncoding,
+) -> Result<(), Error> {
+    let options = ExportOptions {
+        format: Some(Format::Ply(encoding)),
+        ..ExportOptions::default()
+    };
+
+    options.export_parts(slice::from_ref(mesh), path)
 }
 
-fn export_stl(mesh: &Mesh<Point<3>>, path: &Path) -> Result<(), Error> {
-    let points = mesh
-        .triangles()
-        .map(|triangle| triangle.inner.points())
-        .collect::<Vec<_>>();
-
-    let vertices = points.iter().map(|points| {
-        points.map(|point| point.coords.components.map(|s| s.into_f32()))
-    });
-
-    let normals = points
-        .iter()
-        .map(|&points| points.into())
-        .map(|triangle: Triangle<3>| triangle.normal())
-        .map(|vector| vector.components.map(|s| s.into_f32()));
-
-    let triangles = vertices
-        .zip(normals)
-        .map(|([v1, v2, v3], normal)| stl::Triangle {
-            normal,
-            v1,
-            v2,
-            v3,
-            attr_byte_count: 0,
-        })
-        .collect::<Vec<_>>();
-
-    let mut file = File::create(path)?;
-
-    let binary_stl_file = stl::BinaryStlFile {
-        header: stl::BinaryStlHeader {
-            header: [0u8; 80],
-            num_triangles: triangles
-                .len()
-                .try_into()
-                .map_err(|_| Error::InvalidTriangleCount)?,
-        },
-        triangles,
+/// Export the provided mesh to an STL file at the given path
+///
+/// Like [`export`], but allows for choosing the encoding of the STL file. The
+/// file name is used as the name of the model.
+pub fn export_stl(
+    mesh: &Mesh<Point<3>>,
+    path: &Path,
+    encoding: StlEncoding,
+) -> Result<(), Error> {
+    let options = ExportOptions {
+        format: Some(Format::Stl(encoding)),
+        ..ExportOptions::default()
     };
 
-    stl::write_stl(&mut file, &binary_stl_file)?;
+    options.export_parts(slice::from_ref(mesh), path)
+}
+
+/// Export the provided faces to the file at the given path
+///
+/// Unlike [`export`], this function exports the boundary representation of the
+/// model, before it is triangulated. This keeps the geometry exact. It is used
+/// for the file types for which [`requires_brep`] returns `true`:
+///
+/// - STEP (`.step`/`.stp`), for any model. The file name is used as the name
+///   of the model.
+/// - SVG & DXF, for 2D models. All faces must be in the xy-plane. Circles and
+///   arcs are kept as such, instead of being approximated.
+pub fn export_brep(faces: &[Face], path: &Path) -> Result<(), Error> {
+    ExportOptions::default().export_brep(faces, path)
+}
+
+/// Export the provided faces to a STEP file at the given path
+///
+/// Like [`export_brep`], but always uses STEP, regardless of the extension.
+pub fn export_step(faces: &[Face], path: &Path) -> Result<(), Error> {
+    let options = ExportOptions {
+        format: Some(Format::Step),
+        ..ExportOptions::default()
+    };
 
-    Ok(())
+    options.export_brep(faces, path)
+}
+
+/// Indicate whether the file at the given path requires the boundary
+/// representation of the model to export
+///
+/// If this returns `true`, use [`export_brep`] instead of [`export`].
+pub fn requires_brep(path: &Path) -> bool {
+    Format::from_path(path)
+        .map(|format| format.requires_brep())
+        .unwrap_or(false)
 }
 
 /// An error that can occur while exporting
@@ -123,6 +174,18 @@ pub enum Error {
     #[error("unrecognized extension found `{0:?}`")]
     InvalidExtension(String),
 
+    /// Unrecognized unit found
+    #[error("unrecognized unit found `{0:?}`")]
+    InvalidUnit(String),
+
+    /// No format specified, when writing to something other than a file
+    #[error("no format specified")]
+    NoFormat,
+
+    /// Format doesn't support the representation of the model
+    #[error("format `{0:?}` doesn't support this representation of the model")]
+    UnsupportedFormat(Format),
+
     /// I/O error whilst exporting to file
     #[error("I/O error whilst exporting to file")]
     Io(#[from] std::io::Error),
@@ -131,7 +194,85 @@ pub enum Error {
     #[error("maximum triangle count exceeded")]
     InvalidTriangleCount,
 
-    /// Threemf error whilst exporting to 3MF file
-    #[error("threemf error whilst exporting to 3MF file")]
-    ThreeMF(#[from] threemf::Error),
+    /// Maximum size of the exported data exceeded
+    #[error("maximum size of exported data exceeded")]
+    InvalidSize,
+
+    /// JSON error whilst exporting to glTF file
+    #[error("JSON error whilst exporting to glTF file")]
+    Json(#[from] serde_json::Error),
+
+    /// Model can't be exported to a 2D file format
+    #[error("model is not two-dimensional; all faces must be in the xy-plane")]
+    NotInXyPlane,
+
+    /// ZIP error whilst exporting to 3MF file
+    #[error("ZIP error whilst exporting to 3MF file")]
+    Zip(#[from] zip::result::ZipError),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use fj_interop::mesh::{Color, Mesh};
+
+    use crate::{Error, ExportOptions, Format, StlEncoding};
+
+    #[test]
+    fn export_to_writer() -> anyhow::Result<()> {
+        let mut mesh = Mesh::new();
+        mesh.push_triangle(
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+            Color([255, 0, 0, 255]),
+        );
+
+        let mut stl = Vec::new();
+        super::export_to_writer(
+            &mesh,
+            Format::Stl(StlEncoding::Ascii),
+            &mut stl,
+        )?;
+        assert!(String::from_utf8(stl)?.starts_with("solid model\n"));
+
+        let result = super::export_to_writer(&mesh, Format::Step, &mut vec![]);
+        assert!(matches!(
+            result,
+            Err(Error::UnsupportedFormat(Format::Step))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn export_with_name() -> anyhow::Result<()> {
+        let mut mesh = Mesh::new();
+        mesh.push_triangle(
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+            Color([255, 0, 0, 255]),
+        );
+
+        let dir = std::env::temp_dir()
+            .join(format!("fj-export-test-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("out.stl");
+
+        // The name takes precedence over the file name.
+        let options = ExportOptions {
+            name: Some("spacer".to_owned()),
+            ..ExportOptions::default()
+        };
+        options.export_parts(&[mesh], &path)?;
+
+        let stl = fs::read(&path)?;
+        fs::remove_dir_all(&dir)?;
+
+        let header = format!(
+            "Fornjot {} STL export: spacer\0",
+            env!("CARGO_PKG_VERSION")
+        );
+        assert!(stl.starts_with(header.as_bytes()));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,113 @@
+//! Export to Wavefront OBJ
+//!
+//! The colors of the mesh are exported as materials, into a material library
+//! (`.mtl`) that is written next to the `.obj` file. When writing to anything
+//! other than a file, no material library is referenced.
+
+use std::{
+    collections::BTreeSet,
+    io::{self, Write},
+};
+
+use fj_interop::mesh::{Color, Mesh};
+use fj_math::Point;
+
+pub fn write_obj(
+    mesh: &Mesh<Point<3>>,
+    mtl_name: Option<&str>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "# Exported by Fornjot")?;
+    if let Some(mtl_name) = mtl_name {
+        writeln!(out, "mtllib {mtl_name}")?;
+    }
+
+    for vertex in mesh.vertices() {
+        let [x, y, z] = vertex.coords.components;
+        writeln!(out, "v {x} {y} {z}")?;
+    }
+
+    let indices = mesh.indices().collect::<Vec<_>>();
+    let mut current_color = None;
+
+    for (triangle, indices) in mesh.triangles().zip(indices.chunks(3)) {
+        // Only switch materials when necessary, to keep the order of the
+        // triangles intact without bloating the file.
+        if current_color != Some(triangle.color) {
+            writeln!(out, "usemtl {}", material_name(triangle.color))?;
+            current_color = Some(triangle.color);
+        }
+
+        // OBJ indices start at 1.
+        let [a, b, c] = [indices[0], indices[1], indices[2]].map(|i| i + 1);
+        writeln!(out, "f {a} {b} {c}")?;
+    }
+
+    Ok(())
+}
+
+pub fn write_mtl(
+    mesh: &Mesh<Point<3>>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "# Exported by Fornjot")?;
+
+    let colors = mesh
+        .triangles()
+        .map(|triangle| triangle.color)
+        .collect::<BTreeSet<_>>();
+
+    for color in colors {
+        let Color([r, g, b, a]) = color;
+        let [r, g, b, a] = [r, g, b, a].map(|c| f32::from(c) / 255.);
+
+        writeln!(out)?;
+        writeln!(out, "newmtl {}", material_name(color))?;
+        writeln!(out, "Kd {r} {g} {b}")?;
+        writeln!(out, "d {a}")?;
+    }
+
+    Ok(())
+}
+
+fn material_name(Color([r, g, b, a]): Color) -> String {
+    format!("color_{r:02x}{g:02x}{b:02x}{a:02x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_interop::mesh::{Color, Mesh};
+    use fj_math::Point;
+
+    #[test]
+    fn write_obj() -> anyhow::Result<()> {
+        let mut mesh = Mesh::new();
+
+        let [a, b, c, d] =
+            [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]]
+                .map(Point::from);
+        mesh.push_triangle([a, b, c], Color([255, 0, 0, 255]));
+        mesh.push_triangle([a, c, d], Color([0, 0, 255, 128]));
+
+        let mut obj = Vec::new();
+        super::write_obj(&mesh, Some("model.mtl"), &mut obj)?;
+        let obj = String::from_utf8(obj)?;
+
+        // Vertices are shared between triangles.
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("v ")).count(),
+            4
+        );
+        assert!(obj.contains("usemtl color_ff0000ff\nf 1 2 3\n"));
+        assert!(obj.contains("usemtl color_0000ff80\nf 1 3 4\n"));
+
+        let mut mtl = Vec::new();
+        super::write_mtl(&mesh, &mut mtl)?;
+        let mtl = String::from_utf8(mtl)?;
+
+        assert!(mtl.contains("newmtl color_ff0000ff\nKd 1 0 0\nd 1\n"));
+        assert!(mtl.contains("newmtl color_0000ff80\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+//! Options that control how a model is exported
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use fj_interop::mesh::Mesh;
+use fj_kernel::objects::Face;
+use fj_math::Point;
+
+use crate::{
+    dxf, gltf, obj, ply, step, stl, svg, three_mf, Error, Format, Unit,
+};
+
+/// Options for exporting a model
+///
+/// The default options determine the format from the extension of the path
+/// that is exported to, and use the file name as the name of the model.
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions {
+    /// The format to export to
+    ///
+    /// If this is `None`, the format is determined from the extension of the
+    /// path that is exported to. Writing to anything other than a file requires
+    /// a format.
+    pub format: Option<Format>,
+
+    /// The unit of the model's coordinates
+    pub unit: Unit,
+
+    /// The name of the model, for formats that store one
+    ///
+    /// If this is `None`, the file name is used, or `model`, if not exporting
+    /// to a file.
+    pub name: Option<String>,
+}
+
+impl ExportOptions {
+    /// Export the provided parts to the file at the given path
+    ///
+    /// The parts are kept separate, for formats that support this. Those are
+    /// glTF, which exports each part as a separate node, and 3MF, which exports
+    /// each part as a separate object. For all other mesh formats, the parts
+    /// are merged into a single mesh.
+    ///
+    /// OBJ export writes an additional material library next to the exported
+    /// file, with the same name and the extension `.mtl`.
+    ///
+    /// Returns an error, if the format requires the boundary representation of
+    /// the model. Use [`ExportOptions::export_brep`] for those formats.
+    pub fn export_parts(
+        &self,
+        parts: &[Mesh<Point<3>>],
+        path: &Path,
+    ) -> Result<(), Error> {
+        let options = self.for_path(path)?;
+
+        if options.format == Some(Format::Obj) {
+            let mtl_path = path.with_extension("mtl");
+            let mtl_name = mtl_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned());
+
+            let merged;
+            let mesh = match parts {
+                [mesh] => mesh,
+                parts => {
+                    merged = merge(parts);
+                    &merged
+                }
+            };
+
+            let mut mtl = BufWriter::new(File::create(&mtl_path)?);
+            obj::write_mtl(mesh, &mut mtl)?;
+            mtl.flush()?;
+
+            let mut file = BufWriter::new(File::create(path)?);
+            obj::write_obj(mesh, mtl_name.as_deref(), &mut file)?;
+            file.flush()?;
+
+            return Ok(());
+        }
+
+        let mut file = BufWriter::new(File::create(path)?);
+        options.write_parts(parts, &mut file)?;
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Export the provided faces to the file at the given path
+    ///
+    /// Unlike [`ExportOptions::export_parts`], this exports the boundary
+    /// representation of the model, before it is triangulated. This keeps the
+    /// geometry exact. It is used for the formats for which
+    /// [`Format::requires_brep`] returns `true`.
+    pub fn export_brep(
+        &self,
+        faces: &[Face],
+        path: &Path,
+    ) -> Result<(), Error> {
+        let options = self.for_path(path)?;
+
+        let mut file = BufWriter::new(File::create(path)?);
+        options.write_brep(faces, &mut file)?;
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Write the provided parts to the given writer
+    ///
+    /// Like [`ExportOptions::export_parts`], but requires the format to be
+    /// set. OBJ export doesn't write a material library in this case.
+    pub fn write_parts(
+        &self,
+        parts: &[Mesh<Point<3>>],
+        out: &mut impl Write,
+    ) -> Result<(), Error> {
+        let format = self.format.ok_or(Error::NoFormat)?;
+
+        match format {
+            Format::ThreeMf => three_mf::write(parts, self.unit, out),
+            Format::Gltf { binary } => {
+                gltf::write(parts, self.unit, binary, out)
+            }
+            Format::Obj | Format::Ply(_) | Format::Stl(_) => {
+                let merged;
+                let mesh = match parts {
+                    [mesh] => mesh,
+                    parts => {
+                        merged = merge(parts);
+                        &merged
+                    }
+                };
+
+                self.write_mesh(mesh, format, out)
+            }
+            Format::Step | Format::Svg | Format::Dxf => {
+                Err(Error::UnsupportedFormat(format))
+            }
+        }
+    }
+
+    /// Write the provided faces to the given writer
+    ///
+    /// Like [`ExportOptions::export_brep`], but requires the format to be set.
+    pub fn write_brep(
+        &self,
+        faces: &[Face],
+        out: &mut impl Write,
+    ) -> Result<(), Error> {
+        let format = self.format.ok_or(Error::NoFormat)?;
+
+        match format {
+            Format::Step => {
+                step::write(faces, self.name(), self.unit, out)?;
+                Ok(())
+            }
+            Format::Svg => svg::write(faces, self.unit, out),
+            Format::Dxf => dxf::write(faces, self.unit, out),
+            Format::ThreeMf
+            | Format::Gltf { .. }
+            | Format::Obj
+            | Format::Ply(_)
+            | Format::Stl(_) => Err(Error::UnsupportedFormat(format)),
+        }
+    }
+
+    fn write_mesh(
+        &self,
+        mesh: &Mesh<Point<3>>,
+        format: Format,
+        out: &mut impl Write,
+    ) -> Result<(), Error> {
+        match format {
+            Format::Obj => {
+                obj::write_obj(mesh, None, out)?;
+                Ok(())
+            }
+            Format::Ply(encoding) => ply::write(mesh, encoding, out),
+            Format::Stl(encoding) => {
+                stl::write(mesh, self.name(), encoding, out)
+            }
+            format => Err(Error::UnsupportedFormat(format)),
+        }
+    }
+
+    /// Fill in the options that are derived from the path, if not set
+    fn for_path(&self, path: &Path) -> Result<Self, Error> {
+        let format = match self.format {
+            Some(format) => format,
+            None => Format::from_path(path)?,
+        };
+        let name = self.name.clone().or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        });
+
+        Ok(Self {
+            format: Some(format),
+            unit: self.unit,
+            name,
+        })
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or("model")
+    }
+}
+
+fn merge(parts: &[Mesh<Point<3>>]) -> Mesh<Point<3>> {
+    let mut mesh = Mesh::new();
+
+    for part in parts {
+        for triangle in part.triangles() {
+            mesh.push_triangle(triangle.inner, triangle.color);
+        }
+    }
+
+    mesh
+}
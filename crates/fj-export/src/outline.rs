@@ -0,0 +1,217 @@
+//! Outlines of 2D faces, as used by the SVG and DXF exporters
+//!
+//! Both formats describe shapes by their boundaries, and both have dedicated
+//! elements for arcs. This module converts the cycles of faces in the
+//! xy-plane into closed paths, keeping circles and arcs intact.
+
+use fj_interop::mesh::Color;
+use fj_kernel::objects::{Curve, Cycle, Face, Surface, SweptCurve};
+use fj_math::{Point, Scalar};
+
+use crate::Error;
+
+/// The outline of a face
+pub struct Outline {
+    pub color: Color,
+
+    /// The closed paths of the face, exteriors first
+    pub paths: Vec<Path>,
+}
+
+/// A closed path
+pub enum Path {
+    Circle { center: [f64; 2], radius: f64 },
+    Segments(Vec<Segment>),
+}
+
+/// A segment of a closed path
+pub struct Segment {
+    pub start: [f64; 2],
+    pub end: [f64; 2],
+
+    /// The arc between start and end, if the segment is not a straight line
+    pub arc: Option<Arc>,
+}
+
+pub struct Arc {
+    pub center: [f64; 2],
+    pub radius: f64,
+
+    /// The angle covered by the arc, in radians
+    ///
+    /// Positive angles are counter-clockwise, negative angles clockwise.
+    pub angle: f64,
+}
+
+/// Compute the outlines of the given faces
+///
+/// Returns an error, if any of the faces is not in the xy-plane.
+pub fn outlines(faces: &[Face]) -> Result<Vec<Outline>, Error> {
+    faces
+        .iter()
+        .map(|face| {
+            if face.triangles().is_some() || !is_in_xy_plane(face.surface()) {
+                return Err(Error::NotInXyPlane);
+            }
+
+            let paths = face.all_cycles().map(path).collect();
+
+            Ok(Outline {
+                color: face.color(),
+                paths,
+            })
+        })
+        .collect()
+}
+
+/// Compute the bounding box of the given outlines, as `[min, max]`
+pub fn bounds(outlines: &[Outline]) -> [[f64; 2]; 2] {
+    let mut min = [f64::INFINITY; 2];
+    let mut max = [f64::NEG_INFINITY; 2];
+
+    let mut extend = |[x, y]: [f64; 2], radius: f64| {
+        min = [min[0].min(x - radius), min[1].min(y - radius)];
+        max = [max[0].max(x + radius), max[1].max(y + radius)];
+    };
+
+    for path in outlines.iter().flat_map(|outline| &outline.paths) {
+        match path {
+            Path::Circle { center, radius } => extend(*center, *radius),
+            Path::Segments(segments) => {
+                // Arcs are accounted for with their full circle. This might
+                // lead to a bounding box that is larger than necessary, but
+                // never to one that is too small.
+                for segment in segments {
+                    extend(segment.start, 0.);
+                    if let Some(arc) = &segment.arc {
+                        extend(arc.center, arc.radius);
+                    }
+                }
+            }
+        }
+    }
+
+    if min[0] > max[0] {
+        return [[0.; 2]; 2];
+    }
+
+    [min, max]
+}
+
+fn path(cycle: &Cycle) -> Path {
+    let mut segments = Vec::new();
+
+    for edge in cycle.edges() {
+        let curve = *edge.curve().global_form();
+
+        let [a, b] = match edge.vertices().get() {
+            Some(vertices) => vertices,
+            None => {
+                // An edge without vertices is a full circle, which makes up
+                // the whole cycle.
+                let (center, radius) = match curve {
+                    Curve::Circle(circle) => {
+                        (circle.center, circle.a.magnitude())
+                    }
+                    Curve::Line(line) => (line.origin, Scalar::ZERO),
+                };
+
+                return Path::Circle {
+                    center: xy(center),
+                    radius: radius.into_f64(),
+                };
+            }
+        };
+
+        let arc = match curve {
+            Curve::Circle(circle) => {
+                let angle = (b.position().t - a.position().t).into_f64();
+                let direction = circle.a.cross(&circle.b).z.into_f64().signum();
+
+                Some(Arc {
+                    center: xy(circle.center),
+                    radius: circle.a.magnitude().into_f64(),
+                    angle: angle * direction,
+                })
+            }
+            Curve::Line(_) => None,
+        };
+
+        segments.push(Segment {
+            start: xy(a.global().position()),
+            end: xy(b.global().position()),
+            arc,
+        });
+    }
+
+    Path::Segments(segments)
+}
+
+fn is_in_xy_plane(surface: &Surface) -> bool {
+    let Surface::SweptCurve(SweptCurve { curve, path }) = surface;
+
+    match curve {
+        Curve::Line(line) => {
+            line.origin.z == Scalar::ZERO
+                && line.direction.z == Scalar::ZERO
+                && path.z == Scalar::ZERO
+        }
+        Curve::Circle(_) => false,
+    }
+}
+
+fn xy(point: Point<3>) -> [f64; 2] {
+    [point.x.into_f64(), point.y.into_f64()]
+}
+
+#[cfg(test)]
+pub mod tests {
+    use fj_interop::mesh::Color;
+    use fj_kernel::objects::{Cycle, Edge, Face, Surface};
+    use fj_math::Scalar;
+
+    use super::Path;
+
+    /// A square with a square hole, and a circle
+    pub fn faces() -> Vec<Face> {
+        let surface = Surface::xy_plane();
+
+        let square = Face::build(surface)
+            .polygon_from_points([[0., 0.], [4., 0.], [4., 4.], [0., 4.]])
+            .with_hole([[1., 1.], [1., 3.], [3., 3.], [3., 1.]])
+            .into_face()
+            .with_color(Color([255, 0, 0, 255]));
+        let circle = Face::new(surface)
+            .with_exteriors([Cycle::new().with_edges([
+                Edge::build().circle_from_radius(Scalar::from(1.))
+            ])])
+            .with_color(Color([0, 0, 255, 128]));
+
+        vec![square, circle]
+    }
+
+    #[test]
+    fn outlines() -> anyhow::Result<()> {
+        let outlines = super::outlines(&faces())?;
+
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(outlines[0].paths.len(), 2);
+        assert!(matches!(
+            outlines[1].paths[..],
+            [Path::Circle { radius, .. }] if radius == 1.
+        ));
+
+        assert_eq!(super::bounds(&outlines), [[-1., -1.], [4., 4.]]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_3d_faces() {
+        let face = Face::build(Surface::xz_plane())
+            .polygon_from_points([[0., 0.], [1., 0.], [1., 1.]])
+            .into_face();
+
+        assert!(super::outlines(&[face]).is_err());
+    }
+}
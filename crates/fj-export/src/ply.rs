@@ -0,0 +1,174 @@
+//! Export to PLY (Polygon File Format)
+//!
+//! The mesh's colors are defined per triangle, but PLY tools commonly expect
+//! per-vertex colors. Both are written. As vertices are shared between
+//! triangles, a vertex gets the color of the first triangle that uses it.
+
+use std::io::{self, Write};
+
+use fj_interop::mesh::{Color, Mesh};
+use fj_math::Point;
+
+use crate::Error;
+
+/// The encoding of a PLY file
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PlyEncoding {
+    /// Human-readable ASCII encoding
+    Ascii,
+
+    /// Binary encoding, little-endian
+    BinaryLittleEndian,
+}
+
+pub fn write(
+    mesh: &Mesh<Point<3>>,
+    encoding: PlyEncoding,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let vertices = mesh.vertices().collect::<Vec<_>>();
+    let indices = mesh.indices().collect::<Vec<_>>();
+    let triangles = mesh.triangles().collect::<Vec<_>>();
+
+    let mut vertex_colors = vec![None; vertices.len()];
+    for (triangle, indices) in triangles.iter().zip(indices.chunks(3)) {
+        for &index in indices {
+            let color = &mut vertex_colors[index as usize];
+            if color.is_none() {
+                *color = Some(triangle.color);
+            }
+        }
+    }
+
+    let format = match encoding {
+        PlyEncoding::Ascii => "ascii",
+        PlyEncoding::BinaryLittleEndian => "binary_little_endian",
+    };
+
+    writeln!(out, "ply")?;
+    writeln!(out, "format {format} 1.0")?;
+    writeln!(out, "comment Exported by Fornjot")?;
+    writeln!(out, "element vertex {}", vertices.len())?;
+    writeln!(out, "property float x")?;
+    writeln!(out, "property float y")?;
+    writeln!(out, "property float z")?;
+    write_color_properties(out)?;
+    writeln!(out, "element face {}", triangles.len())?;
+    writeln!(out, "property list uchar uint vertex_indices")?;
+    write_color_properties(out)?;
+    writeln!(out, "end_header")?;
+
+    for (vertex, color) in vertices.iter().zip(vertex_colors) {
+        let position = vertex.coords.components.map(|s| s.into_f32());
+        let Color(color) = color.unwrap_or_default();
+
+        match encoding {
+            PlyEncoding::Ascii => {
+                let [x, y, z] = position;
+                let [r, g, b, a] = color;
+                writeln!(out, "{x} {y} {z} {r} {g} {b} {a}")?;
+            }
+            PlyEncoding::BinaryLittleEndian => {
+                for coord in position {
+                    out.write_all(&coord.to_le_bytes())?;
+                }
+                out.write_all(&color)?;
+            }
+        }
+    }
+
+    for (triangle, indices) in triangles.iter().zip(indices.chunks(3)) {
+        let Color(color) = triangle.color;
+
+        match encoding {
+            PlyEncoding::Ascii => {
+                let [a, b, c] = [indices[0], indices[1], indices[2]];
+                let [red, green, blue, alpha] = color;
+                writeln!(out, "3 {a} {b} {c} {red} {green} {blue} {alpha}")?;
+            }
+            PlyEncoding::BinaryLittleEndian => {
+                out.write_all(&[3])?;
+                for &index in indices {
+                    out.write_all(&index.to_le_bytes())?;
+                }
+                out.write_all(&color)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_color_properties(out: &mut impl Write) -> io::Result<()> {
+    for channel in ["red", "green", "blue", "alpha"] {
+        writeln!(out, "property uchar {channel}")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_interop::mesh::{Color, Mesh};
+    use fj_math::Point;
+
+    use super::PlyEncoding;
+
+    #[test]
+    fn write_ascii() -> anyhow::Result<()> {
+        let mesh = square();
+
+        let mut ply = Vec::new();
+        super::write(&mesh, PlyEncoding::Ascii, &mut ply)?;
+        let ply = String::from_utf8(ply)?;
+
+        let body = ply.split("end_header\n").nth(1).unwrap();
+        assert_eq!(
+            body,
+            "0 0 0 255 0 0 255\n\
+            1 0 0 255 0 0 255\n\
+            1 1 0 255 0 0 255\n\
+            0 1 0 0 0 255 255\n\
+            3 0 1 2 255 0 0 255\n\
+            3 0 2 3 0 0 255 255\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_binary() -> anyhow::Result<()> {
+        let mesh = square();
+
+        let mut ply = Vec::new();
+        super::write(&mesh, PlyEncoding::BinaryLittleEndian, &mut ply)?;
+
+        let header_end = b"end_header\n";
+        let body_start = ply
+            .windows(header_end.len())
+            .position(|window| window == header_end)
+            .unwrap()
+            + header_end.len();
+
+        // 4 vertices with 3 floats and 4 color bytes each, 2 faces with a count
+        // byte, 3 indices and 4 color bytes each.
+        assert_eq!(
+            ply.len() - body_start,
+            4 * (3 * 4 + 4) + 2 * (1 + 3 * 4 + 4)
+        );
+
+        Ok(())
+    }
+
+    fn square() -> Mesh<Point<3>> {
+        let mut mesh = Mesh::new();
+
+        let [a, b, c, d] =
+            [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]]
+                .map(Point::from);
+        mesh.push_triangle([a, b, c], Color([255, 0, 0, 255]));
+        mesh.push_triangle([a, c, d], Color([0, 0, 255, 255]));
+
+        mesh
+    }
+}
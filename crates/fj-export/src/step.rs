@@ -0,0 +1,695 @@
+//! Export to STEP (ISO 10303-21), using the AP214 schema
+//!
+//! Unlike the other formats, STEP is not exported from a triangle mesh, but
+//! from the boundary representation of the model. This keeps the geometry
+//! exact.
+//!
+//! Faces are matched up by their edges: Edges with the same vertices (and, for
+//! arcs, the same midpoint) are written only once, and shared between the faces
+//! they bound. If every edge bounds exactly two faces, the faces form a closed
+//! shell, and are exported as a solid. Otherwise, they are exported as a
+//! surface model.
+//!
+//! Faces that use triangle representation are exported as one planar face per
+//! triangle.
+
+use std::{
+    collections::HashMap,
+    f64::consts::TAU,
+    io::{self, Write},
+};
+
+use fj_kernel::objects::{Curve, Cycle, Edge, Face, Surface, SweptCurve};
+use fj_math::{Point, Scalar, Triangle, Vector};
+
+use crate::Unit;
+
+pub fn write(
+    faces: &[Face],
+    name: &str,
+    unit: Unit,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let name = string(name);
+
+    let mut step = Step::default();
+    let context = step.context(unit);
+
+    let mut face_ids = Vec::new();
+    for face in faces {
+        match face.triangles() {
+            Some(triangles) => {
+                for (triangle, _) in triangles {
+                    face_ids.push(step.triangle(triangle));
+                }
+            }
+            None => face_ids.push(step.face(face)),
+        }
+    }
+
+    let closed = step.edges.values().all(|edge| edge.uses == 2);
+    let faces = refs(&face_ids);
+
+    let origin =
+        step.placement(Point::origin(), Vector::unit_z(), Vector::unit_x());
+    let representation = if closed {
+        let shell = step.add(format!("CLOSED_SHELL('',({faces}))"));
+        let brep = step.add(format!("MANIFOLD_SOLID_BREP('{name}',#{shell})"));
+        step.add(format!(
+            "ADVANCED_BREP_SHAPE_REPRESENTATION('',(#{origin},#{brep}),#{context})"
+        ))
+    } else {
+        let shell = step.add(format!("OPEN_SHELL('',({faces}))"));
+        let model =
+            step.add(format!("SHELL_BASED_SURFACE_MODEL('{name}',(#{shell}))"));
+        step.add(format!(
+            "MANIFOLD_SURFACE_SHAPE_REPRESENTATION('',(#{origin},#{model}),#{context})"
+        ))
+    };
+
+    step.product(&name, representation);
+
+    let version = env!("CARGO_PKG_VERSION");
+
+    writeln!(out, "ISO-10303-21;")?;
+    writeln!(out, "HEADER;")?;
+    writeln!(out, "FILE_DESCRIPTION(('Fornjot model'),'2;1');")?;
+    writeln!(
+        out,
+        "FILE_NAME('{name}','',(''),(''),'Fornjot {version}','Fornjot {version}','');"
+    )?;
+    writeln!(
+        out,
+        "FILE_SCHEMA(('AUTOMOTIVE_DESIGN {{ 1 0 10303 214 1 1 1 1 }}'));"
+    )?;
+    writeln!(out, "ENDSEC;")?;
+    writeln!(out, "DATA;")?;
+    for (i, entity) in step.entities.iter().enumerate() {
+        writeln!(out, "#{}={entity};", i + 1)?;
+    }
+    writeln!(out, "ENDSEC;")?;
+    writeln!(out, "END-ISO-10303-21;")?;
+
+    Ok(())
+}
+
+/// The entities of a STEP file, as they are being built
+#[derive(Default)]
+struct Step {
+    entities: Vec<String>,
+    vertices: HashMap<Point<3>, usize>,
+    edges: HashMap<EdgeKey, EdgeCurve>,
+}
+
+impl Step {
+    /// Add an entity and return its ID
+    fn add(&mut self, entity: impl Into<String>) -> usize {
+        self.entities.push(entity.into());
+
+        // IDs start at 1.
+        self.entities.len()
+    }
+
+    fn context(&mut self, unit: Unit) -> usize {
+        let length = self.length_unit(unit);
+        let angle =
+            self.add("(NAMED_UNIT(*)PLANE_ANGLE_UNIT()SI_UNIT($,.RADIAN.))");
+        let solid_angle =
+            self.add("(NAMED_UNIT(*)SI_UNIT($,.STERADIAN.)SOLID_ANGLE_UNIT())");
+        let uncertainty = self.add(format!(
+            "UNCERTAINTY_MEASURE_WITH_UNIT(LENGTH_MEASURE(1.E-07),#{length},\
+            'distance_accuracy_value','confusion accuracy')"
+        ));
+
+        self.add(format!(
+            "(GEOMETRIC_REPRESENTATION_CONTEXT(3)\
+            GLOBAL_UNCERTAINTY_ASSIGNED_CONTEXT((#{uncertainty}))\
+            GLOBAL_UNIT_ASSIGNED_CONTEXT((#{length},#{angle},#{solid_angle}))\
+            REPRESENTATION_CONTEXT('',''))"
+        ))
+    }
+
+    fn length_unit(&mut self, unit: Unit) -> usize {
+        let prefix = match unit {
+            Unit::Millimeter | Unit::Inch => ".MILLI.",
+            Unit::Centimeter => ".CENTI.",
+            Unit::Meter => "$",
+        };
+        let si_unit = self.add(format!(
+            "(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT({prefix},.METRE.))"
+        ));
+
+        if unit != Unit::Inch {
+            return si_unit;
+        }
+
+        // Inches are not an SI unit, so they are defined in terms of one.
+        let measure = self.add(format!(
+            "LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(25.4),#{si_unit})"
+        ));
+        let exponents = self.add("DIMENSIONAL_EXPONENTS(1.,0.,0.,0.,0.,0.,0.)");
+        self.add(format!(
+            "(CONVERSION_BASED_UNIT('INCH',#{measure})LENGTH_UNIT()\
+            NAMED_UNIT(#{exponents}))"
+        ))
+    }
+
+    fn product(&mut self, name: &str, representation: usize) {
+        let application_context = self.add(
+            "APPLICATION_CONTEXT(\
+            'core data for automotive mechanical design processes')",
+        );
+        self.add(format!(
+            "APPLICATION_PROTOCOL_DEFINITION('international standard',\
+            'automotive_design',2000,#{application_context})"
+        ));
+        let product_context = self.add(format!(
+            "PRODUCT_CONTEXT('',#{application_context},'mechanical')"
+        ));
+        let definition_context = self.add(format!(
+            "PRODUCT_DEFINITION_CONTEXT('part definition',\
+            #{application_context},'design')"
+        ));
+        let product = self.add(format!(
+            "PRODUCT('{name}','{name}','',(#{product_context}))"
+        ));
+        self.add(format!(
+            "PRODUCT_RELATED_PRODUCT_CATEGORY('part',$,(#{product}))"
+        ));
+        let formation =
+            self.add(format!("PRODUCT_DEFINITION_FORMATION('','',#{product})"));
+        let definition = self.add(format!(
+            "PRODUCT_DEFINITION('design','',#{formation},#{definition_context})"
+        ));
+        let shape =
+            self.add(format!("PRODUCT_DEFINITION_SHAPE('','',#{definition})"));
+        self.add(format!(
+            "SHAPE_DEFINITION_REPRESENTATION(#{shape},#{representation})"
+        ));
+    }
+
+    fn face(&mut self, face: &Face) -> usize {
+        let (surface, same_sense) = self.surface(*face.surface());
+
+        let mut bounds = Vec::new();
+        for (i, cycle) in face.exteriors().enumerate() {
+            // The outer bound must run counter-clockwise around the face's
+            // normal, any other bound clockwise.
+            let orientation = is_counter_clockwise(cycle);
+            let edge_loop = self.edge_loop(cycle);
+            let bound = if i == 0 {
+                "FACE_OUTER_BOUND"
+            } else {
+                "FACE_BOUND"
+            };
+
+            bounds.push(self.add(format!(
+                "{bound}('',#{edge_loop},{})",
+                logical(orientation)
+            )));
+        }
+        for cycle in face.interiors() {
+            let orientation = !is_counter_clockwise(cycle);
+            let edge_loop = self.edge_loop(cycle);
+
+            bounds.push(self.add(format!(
+                "FACE_BOUND('',#{edge_loop},{})",
+                logical(orientation)
+            )));
+        }
+
+        self.add(format!(
+            "ADVANCED_FACE('',({}),#{surface},{})",
+            refs(&bounds),
+            logical(same_sense)
+        ))
+    }
+
+    fn triangle(&mut self, triangle: &Triangle<3>) -> usize {
+        let [a, b, c] = triangle.points();
+
+        let placement = self.placement(a, triangle.normal(), b - a);
+        let plane = self.add(format!("PLANE('',#{placement})"));
+
+        let oriented_edges = [[a, b], [b, c], [c, a]]
+            .map(|points| {
+                let line = Curve::line_from_points(points);
+                let (edge, orientation) =
+                    self.bounded_edge(points, None, line, true);
+                self.oriented_edge(edge, orientation)
+            })
+            .to_vec();
+
+        let edge_loop =
+            self.add(format!("EDGE_LOOP('',({}))", refs(&oriented_edges)));
+        let bound = self.add(format!("FACE_OUTER_BOUND('',#{edge_loop},.T.)"));
+
+        self.add(format!("ADVANCED_FACE('',(#{bound}),#{plane},.T.)"))
+    }
+
+    /// Add a surface
+    ///
+    /// Also returns whether the normal of the STEP surface points in the same
+    /// direction as the normal of the Fornjot surface.
+    fn surface(&mut self, surface: Surface) -> (usize, bool) {
+        let Surface::SweptCurve(SweptCurve { curve, path }) = surface;
+
+        match curve {
+            Curve::Line(line) => {
+                let normal = line.direction.cross(&path);
+                let placement =
+                    self.placement(line.origin, normal, line.direction);
+
+                (self.add(format!("PLANE('',#{placement})")), true)
+            }
+            Curve::Circle(circle)
+                if is_parallel(path, circle.a.cross(&circle.b)) =>
+            {
+                let axis = circle.a.cross(&circle.b);
+                let placement = self.placement(circle.center, axis, circle.a);
+                let radius = real(circle.a.magnitude());
+                let cylinder = self.add(format!(
+                    "CYLINDRICAL_SURFACE('',#{placement},{radius})"
+                ));
+
+                // The normal of a cylindrical surface points away from its
+                // axis. Check the normal of the swept curve at its start.
+                let normal = circle.b.cross(&path);
+                let same_sense = normal.dot(&circle.a) > Scalar::ZERO;
+
+                (cylinder, same_sense)
+            }
+            Curve::Circle(_) => {
+                let curve = self.curve(curve);
+                let vector = self.vector(path);
+
+                (
+                    self.add(format!(
+                        "SURFACE_OF_LINEAR_EXTRUSION('',#{curve},#{vector})"
+                    )),
+                    true,
+                )
+            }
+        }
+    }
+
+    fn edge_loop(&mut self, cycle: &Cycle) -> usize {
+        let oriented_edges = cycle
+            .edges()
+            .map(|edge| {
+                let (edge, orientation) = self.edge(edge);
+                self.oriented_edge(edge, orientation)
+            })
+            .collect::<Vec<_>>();
+
+        self.add(format!("EDGE_LOOP('',({}))", refs(&oriented_edges)))
+    }
+
+    fn oriented_edge(&mut self, edge: usize, orientation: bool) -> usize {
+        self.add(format!(
+            "ORIENTED_EDGE('',*,*,#{edge},{})",
+            logical(orientation)
+        ))
+    }
+
+    /// Add an edge curve, or reuse an existing one
+    ///
+    /// Also returns whether the edge runs in the same direction as the edge
+    /// curve.
+    fn edge(&mut self, edge: &Edge) -> (usize, bool) {
+        let curve = *edge.curve().global_form();
+
+        match edge.vertices().get() {
+            Some([a, b]) => {
+                let same_sense = a.position() < b.position();
+
+                // Arcs with the same endpoints can still be different parts of
+                // the same circle. The point halfway between the vertices
+                // tells them apart.
+                let midpoint = match curve {
+                    Curve::Circle(_) => {
+                        let t = (a.position().t + b.position().t) / 2.;
+                        Some(curve.point_from_curve_coords([t]))
+                    }
+                    Curve::Line(_) => None,
+                };
+
+                self.bounded_edge(
+                    [a.global().position(), b.global().position()],
+                    midpoint,
+                    curve,
+                    same_sense,
+                )
+            }
+            None => self.continuous_edge(curve),
+        }
+    }
+
+    fn bounded_edge(
+        &mut self,
+        [start, end]: [Point<3>; 2],
+        midpoint: Option<Point<3>>,
+        curve: Curve<3>,
+        same_sense: bool,
+    ) -> (usize, bool) {
+        let mut vertices = [start, end];
+        vertices.sort();
+
+        let key = EdgeKey::Bounded { vertices, midpoint };
+        let direction = end - start;
+
+        if let Some(edge) = self.edges.get_mut(&key) {
+            edge.uses += 1;
+            return (edge.id, edge.direction.dot(&direction) > Scalar::ZERO);
+        }
+
+        let start = self.vertex(start);
+        let end = self.vertex(end);
+        let curve = self.curve(curve);
+        let id = self.add(format!(
+            "EDGE_CURVE('',#{start},#{end},#{curve},{})",
+            logical(same_sense)
+        ));
+
+        self.edges.insert(
+            key,
+            EdgeCurve {
+                id,
+                direction,
+                uses: 1,
+            },
+        );
+
+        (id, true)
+    }
+
+    /// Add an edge curve for an edge that has no vertices, like a full circle
+    ///
+    /// STEP still requires a vertex, so one is added at the start of the
+    /// curve.
+    fn continuous_edge(&mut self, curve: Curve<3>) -> (usize, bool) {
+        let (radius, direction) = match curve {
+            Curve::Circle(circle) => {
+                (circle.a.magnitude(), circle.a.cross(&circle.b))
+            }
+            Curve::Line(line) => (Scalar::ZERO, line.direction),
+        };
+
+        // Edges that use the same curve might run in opposite directions, so
+        // the axis is only compared up to its sign.
+        let axis = direction.normalize();
+        let key = EdgeKey::Continuous {
+            center: curve.origin(),
+            radius,
+            axis: axis.max(-axis),
+        };
+
+        if let Some(edge) = self.edges.get_mut(&key) {
+            edge.uses += 1;
+            return (edge.id, edge.direction.dot(&direction) > Scalar::ZERO);
+        }
+
+        let vertex = self.vertex(curve.point_from_curve_coords([0.]));
+        let curve = self.curve(curve);
+        let id = self
+            .add(format!("EDGE_CURVE('',#{vertex},#{vertex},#{curve},.T.)"));
+
+        self.edges.insert(
+            key,
+            EdgeCurve {
+                id,
+                direction,
+                uses: 1,
+            },
+        );
+
+        (id, true)
+    }
+
+    fn curve(&mut self, curve: Curve<3>) -> usize {
+        match curve {
+            Curve::Line(line) => {
+                let origin = self.point(line.origin);
+                let vector = self.vector(line.direction);
+                self.add(format!("LINE('',#{origin},#{vector})"))
+            }
+            Curve::Circle(circle) => {
+                // STEP circles run counter-clockwise around their axis. This
+                // is the case for the axis `a x b`.
+                let placement = self.placement(
+                    circle.center,
+                    circle.a.cross(&circle.b),
+                    circle.a,
+                );
+                let radius = real(circle.a.magnitude());
+                self.add(format!("CIRCLE('',#{placement},{radius})"))
+            }
+        }
+    }
+
+    fn vertex(&mut self, position: Point<3>) -> usize {
+        if let Some(&id) = self.vertices.get(&position) {
+            return id;
+        }
+
+        let point = self.point(position);
+        let id = self.add(format!("VERTEX_POINT('',#{point})"));
+        self.vertices.insert(position, id);
+
+        id
+    }
+
+    fn placement(
+        &mut self,
+        location: Point<3>,
+        axis: Vector<3>,
+        ref_direction: Vector<3>,
+    ) -> usize {
+        let location = self.point(location);
+        let axis = self.direction(axis);
+        let ref_direction = self.direction(ref_direction);
+
+        self.add(format!(
+            "AXIS2_PLACEMENT_3D('',#{location},#{axis},#{ref_direction})"
+        ))
+    }
+
+    fn point(&mut self, point: Point<3>) -> usize {
+        let [x, y, z] = point.coords.components.map(real);
+        self.add(format!("CARTESIAN_POINT('',({x},{y},{z}))"))
+    }
+
+    fn direction(&mut self, direction: Vector<3>) -> usize {
+        let [x, y, z] = direction.normalize().components.map(real);
+        self.add(format!("DIRECTION('',({x},{y},{z}))"))
+    }
+
+    fn vector(&mut self, vector: Vector<3>) -> usize {
+        let direction = self.direction(vector);
+        let magnitude = real(vector.magnitude());
+        self.add(format!("VECTOR('',#{direction},{magnitude})"))
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+enum EdgeKey {
+    Bounded {
+        vertices: [Point<3>; 2],
+
+        /// The point halfway along the edge, if it is an arc
+        midpoint: Option<Point<3>>,
+    },
+    Continuous {
+        center: Point<3>,
+        radius: Scalar,
+        axis: Vector<3>,
+    },
+}
+
+struct EdgeCurve {
+    id: usize,
+
+    /// The direction of the edge curve, for comparison with other edges
+    ///
+    /// For bounded edges, this is the vector from start to end. For continuous
+    /// edges, it is the axis of the circle.
+    direction: Vector<3>,
+
+    /// The number of edges that use this edge curve
+    uses: usize,
+}
+
+/// Determine the winding of a cycle, in surface coordinates
+fn is_counter_clockwise(cycle: &Cycle) -> bool {
+    // Arcs are sampled, as their endpoints alone don't determine the winding.
+    const ARC_SAMPLES: u64 = 16;
+
+    let mut points = Vec::new();
+    for edge in cycle.edges() {
+        let curve = edge.curve().local_form();
+
+        let range = match edge.vertices().get() {
+            Some([a, b]) => [a.position().t, b.position().t],
+            None => [Scalar::ZERO, Scalar::from(TAU)],
+        };
+        let samples = match curve {
+            Curve::Circle(_) => ARC_SAMPLES,
+            Curve::Line(_) => 1,
+        };
+
+        let [start, end] = range;
+        for i in 0..samples {
+            let t = start
+                + (end - start)
+                    * (Scalar::from_u64(i) / Scalar::from_u64(samples));
+            points.push(curve.point_from_curve_coords([t]));
+        }
+    }
+
+    // Shoelace formula
+    let mut area = Scalar::ZERO;
+    for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+        area += a.u * b.v - b.u * a.v;
+    }
+
+    area > Scalar::ZERO
+}
+
+fn is_parallel(a: Vector<3>, b: Vector<3>) -> bool {
+    a.normalize().cross(&b.normalize()).magnitude() < Scalar::from(1e-9)
+}
+
+fn refs(ids: &[usize]) -> String {
+    ids.iter()
+        .map(|id| format!("#{id}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn logical(value: bool) -> &'static str {
+    if value {
+        ".T."
+    } else {
+        ".F."
+    }
+}
+
+/// Format a real number, as STEP expects it
+///
+/// STEP requires a decimal point in every real number, even if it uses
+/// exponent notation.
+fn real(value: Scalar) -> String {
+    let value = format!("{:?}", value.into_f64());
+
+    match value.split_once('e') {
+        Some((mantissa, exponent)) => {
+            let point = if mantissa.contains('.') { "" } else { "." };
+            format!("{mantissa}{point}E{exponent}")
+        }
+        None => value,
+    }
+}
+
+/// Encode a string, as STEP expects it
+///
+/// Apostrophes are escaped. Non-ASCII characters would require a special
+/// encoding, so they are replaced instead.
+fn string(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_ascii() { c } else { '_' })
+        .collect::<String>()
+        .replace('\'', "''")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use fj_kernel::objects::{Curve, Solid};
+    use fj_math::{Circle, Point, Scalar, Vector};
+
+    use crate::Unit;
+
+    #[test]
+    fn write_cube() -> anyhow::Result<()> {
+        let faces = Solid::build()
+            .cube_from_edge_length(1.)
+            .into_faces()
+            .collect::<Vec<_>>();
+
+        let mut step = Vec::new();
+        super::write(&faces, "cube", Unit::Millimeter, &mut step)?;
+        let step = String::from_utf8(step)?;
+
+        let count = |entity: &str| {
+            step.lines()
+                .filter(|line| line.contains(&format!("={entity}(")))
+                .count()
+        };
+
+        // Edges and vertices are shared between faces.
+        assert_eq!(count("ADVANCED_FACE"), 6);
+        assert_eq!(count("EDGE_CURVE"), 12);
+        assert_eq!(count("VERTEX_POINT"), 8);
+        assert_eq!(count("ORIENTED_EDGE"), 24);
+
+        // Every edge bounds two faces, so the cube is a solid.
+        assert_eq!(count("CLOSED_SHELL"), 1);
+        assert_eq!(count("MANIFOLD_SOLID_BREP"), 1);
+
+        assert!(step.starts_with("ISO-10303-21;\n"));
+        assert!(step.ends_with("END-ISO-10303-21;\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn edge_keys() {
+        let mut step = super::Step::default();
+
+        let circle = |b| {
+            Curve::Circle(Circle {
+                center: Point::origin(),
+                a: Vector::unit_x(),
+                b,
+            })
+        };
+        let xy = circle(Vector::unit_y());
+        let xz = circle(Vector::unit_z());
+
+        // Minor and major arc between the same vertices.
+        let vertices = [Point::from([1., 0., 0.]), Point::from([0., 1., 0.])];
+        let (minor, _) = step.bounded_edge(
+            vertices,
+            Some(xy.point_from_curve_coords([PI / 4.])),
+            xy,
+            true,
+        );
+        let (major, _) = step.bounded_edge(
+            vertices,
+            Some(xy.point_from_curve_coords([PI * 5. / 4.])),
+            xy,
+            true,
+        );
+        assert_ne!(minor, major);
+
+        // Full circles with the same center and radius, but different axes.
+        let (in_xy, _) = step.continuous_edge(xy);
+        let (in_xz, _) = step.continuous_edge(xz);
+        assert_ne!(in_xy, in_xz);
+
+        // The same circle, running in the opposite direction.
+        let (reversed, same_sense) = step.continuous_edge(xy.reverse());
+        assert_eq!(reversed, in_xy);
+        assert!(!same_sense);
+    }
+
+    #[test]
+    fn real() {
+        assert_eq!(super::real(Scalar::from(1.)), "1.0");
+        assert_eq!(super::real(Scalar::from(-0.5)), "-0.5");
+        assert_eq!(super::real(Scalar::from(1e-7)), "1.E-7");
+        assert_eq!(super::real(Scalar::from(2.5e20)), "2.5E20");
+    }
+}
@@ -0,0 +1,169 @@
+//! Export to STL, using either the ASCII or the binary encoding
+//!
+//! The binary encoding can optionally store the color of each triangle in its
+//! attribute bytes. This follows the convention used by VisCAM and SolidView:
+//! 5 bits per channel, blue in the lowest bits, and the highest bit set to
+//! mark the color as valid. The alpha channel is not stored.
+
+use std::io::Write;
+
+use fj_interop::mesh::{Color, Mesh};
+use fj_math::Point;
+
+use crate::Error;
+
+/// The encoding of an STL file
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StlEncoding {
+    /// Human-readable ASCII encoding
+    Ascii,
+
+    /// Binary encoding
+    Binary,
+
+    /// Binary encoding, with the color of each triangle in its attribute bytes
+    BinaryWithColor,
+}
+
+pub fn write(
+    mesh: &Mesh<Point<3>>,
+    name: &str,
+    encoding: StlEncoding,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let triangles = mesh.triangles().map(|triangle| {
+        let vertices = triangle
+            .inner
+            .points()
+            .map(|point| point.coords.components.map(|s| s.into_f32()));
+        let normal = triangle.inner.normal().components.map(|s| s.into_f32());
+
+        (normal, vertices, triangle.color)
+    });
+
+    match encoding {
+        StlEncoding::Ascii => {
+            // Whitespace would end the name early, when the file is read.
+            let name = name.replace(char::is_whitespace, "_");
+
+            writeln!(out, "solid {name}")?;
+            for ([nx, ny, nz], vertices, _) in triangles {
+                writeln!(out, "  facet normal {nx:e} {ny:e} {nz:e}")?;
+                writeln!(out, "    outer loop")?;
+                for [x, y, z] in vertices {
+                    writeln!(out, "      vertex {x:e} {y:e} {z:e}")?;
+                }
+                writeln!(out, "    endloop")?;
+                writeln!(out, "  endfacet")?;
+            }
+            writeln!(out, "endsolid {name}")?;
+        }
+        StlEncoding::Binary | StlEncoding::BinaryWithColor => {
+            let triangles = triangles
+                .map(|(normal, [v1, v2, v3], color)| {
+                    let attr_byte_count =
+                        if encoding == StlEncoding::BinaryWithColor {
+                            encode_color(color)
+                        } else {
+                            0
+                        };
+
+                    ::stl::Triangle {
+                        normal,
+                        v1,
+                        v2,
+                        v3,
+                        attr_byte_count,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let binary_stl_file = ::stl::BinaryStlFile {
+                header: ::stl::BinaryStlHeader {
+                    header: header(name),
+                    num_triangles: triangles
+                        .len()
+                        .try_into()
+                        .map_err(|_| Error::InvalidTriangleCount)?,
+                },
+                triangles,
+            };
+
+            ::stl::write_stl(out, &binary_stl_file)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Create the header of a binary STL file
+///
+/// The header must not start with `solid`, or the file might be mistaken for
+/// an ASCII STL file. Starting with the name of the application prevents that.
+fn header(name: &str) -> [u8; 80] {
+    let text =
+        format!("Fornjot {} STL export: {name}", env!("CARGO_PKG_VERSION"));
+
+    let mut header = [0; 80];
+    for (byte, &text) in header.iter_mut().zip(text.as_bytes()) {
+        *byte = text;
+    }
+
+    header
+}
+
+fn encode_color(Color([r, g, b, _]): Color) -> u16 {
+    let [r, g, b] = [r, g, b].map(|channel| u16::from(channel >> 3));
+    1 << 15 | r << 10 | g << 5 | b
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_interop::mesh::{Color, Mesh};
+    use fj_math::Point;
+
+    use super::StlEncoding;
+
+    #[test]
+    fn write_ascii() -> anyhow::Result<()> {
+        let mut stl = Vec::new();
+        super::write(&triangle(), "my model", StlEncoding::Ascii, &mut stl)?;
+        let stl = String::from_utf8(stl)?;
+
+        assert!(stl.starts_with("solid my_model\n"));
+        assert!(stl.contains("  facet normal 0e0 0e0 1e0\n"));
+        assert!(stl.contains("      vertex 1e0 0e0 0e0\n"));
+        assert!(stl.ends_with("  endfacet\nendsolid my_model\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_binary() -> anyhow::Result<()> {
+        let mut stl = Vec::new();
+        super::write(
+            &triangle(),
+            "model",
+            StlEncoding::BinaryWithColor,
+            &mut stl,
+        )?;
+
+        assert!(stl.starts_with(b"Fornjot "));
+        assert_eq!(stl.len(), 80 + 4 + 50);
+
+        // The attribute bytes are the last two bytes of the triangle.
+        let attr = u16::from_le_bytes([stl[132], stl[133]]);
+        assert_eq!(attr, 0b1_11111_00000_10000);
+
+        Ok(())
+    }
+
+    fn triangle() -> Mesh<Point<3>> {
+        let mut mesh = Mesh::new();
+        mesh.push_triangle(
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+            Color([255, 0, 128, 255]),
+        );
+        mesh
+    }
+}
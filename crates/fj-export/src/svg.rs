@@ -0,0 +1,160 @@
+//! Export of 2D faces to SVG
+//!
+//! Each face becomes a path, with the face's color as fill and stroke. Holes
+//! are cut out using the even-odd fill rule. The size of the image is given in
+//! the unit of the model, and the y-axis is flipped, as it points down in SVG.
+
+use std::{
+    f64::consts::PI,
+    io::{self, Write},
+};
+
+use fj_interop::mesh::Color;
+use fj_kernel::objects::Face;
+
+use crate::{
+    outline::{self, Outline},
+    Error, Unit,
+};
+
+pub fn write(
+    faces: &[Face],
+    unit: Unit,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let outlines = outline::outlines(faces)?;
+    write_outlines(&outlines, unit, out)?;
+
+    Ok(())
+}
+
+fn write_outlines(
+    outlines: &[Outline],
+    unit: Unit,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let [[min_x, min_y], [max_x, max_y]] = outline::bounds(outlines);
+    let [width, height] = [max_x - min_x, max_y - min_y];
+
+    // SVG doesn't support meters, so those are converted to centimeters.
+    let (unit, scale) = match unit {
+        Unit::Millimeter => ("mm", 1.),
+        Unit::Centimeter => ("cm", 1.),
+        Unit::Meter => ("cm", 100.),
+        Unit::Inch => ("in", 1.),
+    };
+    let [physical_width, physical_height] = [width * scale, height * scale];
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{physical_width}{unit}" height="{physical_height}{unit}" viewBox="{min_x} {} {width} {height}">"#,
+        0. - max_y,
+    )?;
+
+    for outline in outlines {
+        let Color([r, g, b, a]) = outline.color;
+        let color = format!("#{r:02x}{g:02x}{b:02x}");
+        let opacity = f64::from(a) / 255.;
+
+        write!(out, r#"  <path d=""#)?;
+        write_path_data(outline, out)?;
+        writeln!(
+            out,
+            r#"" fill="{color}" fill-opacity="{opacity}" fill-rule="evenodd" stroke="{color}" stroke-width="0.1"/>"#,
+        )?;
+    }
+
+    writeln!(out, "</svg>")?;
+
+    Ok(())
+}
+
+fn write_path_data(outline: &Outline, out: &mut impl Write) -> io::Result<()> {
+    for (i, path) in outline.paths.iter().enumerate() {
+        if i > 0 {
+            write!(out, " ")?;
+        }
+
+        match path {
+            outline::Path::Circle { center, radius } => {
+                // SVG has no command for a full circle, so it's split into two
+                // arcs.
+                let [x, y] = flip(*center);
+                let r = radius;
+                let (left, right) = (x - r, x + r);
+
+                write!(
+                    out,
+                    "M {right} {y} A {r} {r} 0 1 0 {left} {y} \
+                    A {r} {r} 0 1 0 {right} {y} Z"
+                )?;
+            }
+            outline::Path::Segments(segments) => {
+                for (j, segment) in segments.iter().enumerate() {
+                    if j == 0 {
+                        let [x, y] = flip(segment.start);
+                        write!(out, "M {x} {y} ")?;
+                    }
+
+                    let [x, y] = flip(segment.end);
+                    match &segment.arc {
+                        Some(arc) => {
+                            let r = arc.radius;
+                            let large_arc = u8::from(arc.angle.abs() > PI);
+
+                            // The y-axis is flipped, so counter-clockwise
+                            // arcs in model space end up going in the
+                            // direction of positive angles in SVG space.
+                            let sweep = u8::from(arc.angle > 0.);
+
+                            write!(
+                                out,
+                                "A {r} {r} 0 {large_arc} {sweep} {x} {y} "
+                            )?;
+                        }
+                        None if j == segments.len() - 1 => {
+                            // Closing the path draws the last line.
+                        }
+                        None => {
+                            write!(out, "L {x} {y} ")?;
+                        }
+                    }
+                }
+
+                write!(out, "Z")?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn flip([x, y]: [f64; 2]) -> [f64; 2] {
+    // Subtracting from zero instead of negating avoids writing `-0`.
+    [x, 0. - y]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{outline::tests::faces, Unit};
+
+    #[test]
+    fn write() -> anyhow::Result<()> {
+        let mut svg = Vec::new();
+        super::write(&faces(), Unit::Millimeter, &mut svg)?;
+        let svg = String::from_utf8(svg)?;
+
+        assert!(svg.contains(r#"width="5mm" height="5mm" viewBox="-1 -4 5 5""#));
+        assert_eq!(svg.matches("<path ").count(), 2);
+
+        // The square with its hole is one path, with two subpaths.
+        assert!(svg.contains(r#"d="M 0 0 L 4 0 L 4 -4 L 0 -4 Z M 1 -1 "#));
+
+        // The circle is made of arcs, not approximated.
+        assert!(svg.contains("M 1 0 A 1 1 0 1 0 -1 0 A 1 1 0 1 0 1 0 Z"));
+        assert!(svg.contains(r##"fill="#0000ff" fill-opacity="0.5019"##));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+//! Export to 3MF (3D Manufacturing Format)
+//!
+//! Each part becomes a separate object, with its own build item, so slicers can
+//! arrange the parts individually.
+//!
+//! Colors are written using the color groups of the 3MF materials extension.
+//! All colors of the model are collected into a single color group, and each
+//! triangle refers to its color by index. Consumers that don't support the
+//! extension still read the geometry.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Cursor, Write},
+};
+
+use fj_interop::mesh::{Color, Mesh};
+use fj_math::Point;
+use zip::{write::FileOptions, ZipWriter};
+
+use crate::{Error, Unit};
+
+pub fn write(
+    parts: &[Mesh<Point<3>>],
+    unit: Unit,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    // Writing a ZIP archive requires seeking, so it's written to memory first.
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    zip.start_file("[Content_Types].xml", FileOptions::default())?;
+    zip.write_all(CONTENT_TYPES.as_bytes())?;
+
+    zip.start_file("_rels/.rels", FileOptions::default())?;
+    zip.write_all(RELATIONSHIPS.as_bytes())?;
+
+    zip.start_file("3D/3dmodel.model", FileOptions::default())?;
+    write_model(parts, unit, &mut zip)?;
+
+    let archive = zip.finish()?.into_inner();
+    out.write_all(&archive)?;
+
+    Ok(())
+}
+
+fn write_model(
+    parts: &[Mesh<Point<3>>],
+    unit: Unit,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    // Resource IDs start at 1. The color group comes first, followed by one
+    // object per part.
+    const COLOR_GROUP_ID: usize = 1;
+
+    let mut colors = BTreeMap::new();
+    for part in parts {
+        for triangle in part.triangles() {
+            let next_index = colors.len();
+            colors.entry(triangle.color).or_insert(next_index);
+        }
+    }
+
+    // Objects without triangles are not valid in 3MF.
+    let parts = parts
+        .iter()
+        .filter(|part| part.triangles().next().is_some())
+        .collect::<Vec<_>>();
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<model unit="{}" xml:lang="en-US" xmlns="{CORE}" xmlns:m="{MATERIALS}">"#,
+        unit_name(unit),
+    )?;
+    writeln!(out, r#"  <metadata name="Application">Fornjot</metadata>"#)?;
+    writeln!(out, "  <resources>")?;
+
+    if !colors.is_empty() {
+        let mut colors_by_index = colors.iter().collect::<Vec<_>>();
+        colors_by_index.sort_by_key(|&(_, index)| index);
+
+        writeln!(out, r#"    <m:colorgroup id="{COLOR_GROUP_ID}">"#)?;
+        for (&color, _) in colors_by_index {
+            writeln!(out, r#"      <m:color color="{}"/>"#, hex(color))?;
+        }
+        writeln!(out, "    </m:colorgroup>")?;
+    }
+
+    for (i, part) in parts.iter().enumerate() {
+        let id = COLOR_GROUP_ID + 1 + i;
+        let first_color = part
+            .triangles()
+            .next()
+            .map(|triangle| colors[&triangle.color])
+            .expect("Empty parts have been filtered out");
+
+        writeln!(
+            out,
+            r#"    <object id="{id}" name="part-{i}" type="model" pid="{COLOR_GROUP_ID}" pindex="{first_color}">"#,
+        )?;
+        writeln!(out, "      <mesh>")?;
+
+        writeln!(out, "        <vertices>")?;
+        for vertex in part.vertices() {
+            let [x, y, z] = vertex.coords.components;
+            writeln!(out, r#"          <vertex x="{x}" y="{y}" z="{z}"/>"#)?;
+        }
+        writeln!(out, "        </vertices>")?;
+
+        writeln!(out, "        <triangles>")?;
+        let indices = part.indices().collect::<Vec<_>>();
+        for (triangle, indices) in part.triangles().zip(indices.chunks(3)) {
+            let [v1, v2, v3] = [indices[0], indices[1], indices[2]];
+            let color = colors[&triangle.color];
+            writeln!(
+                out,
+                r#"          <triangle v1="{v1}" v2="{v2}" v3="{v3}" pid="{COLOR_GROUP_ID}" p1="{color}"/>"#,
+            )?;
+        }
+        writeln!(out, "        </triangles>")?;
+
+        writeln!(out, "      </mesh>")?;
+        writeln!(out, "    </object>")?;
+    }
+
+    writeln!(out, "  </resources>")?;
+    writeln!(out, "  <build>")?;
+    for i in 0..parts.len() {
+        let id = COLOR_GROUP_ID + 1 + i;
+        writeln!(out, r#"    <item objectid="{id}"/>"#)?;
+    }
+    writeln!(out, "  </build>")?;
+    writeln!(out, "</model>")?;
+
+    Ok(())
+}
+
+fn unit_name(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Millimeter => "millimeter",
+        Unit::Centimeter => "centimeter",
+        Unit::Meter => "meter",
+        Unit::Inch => "inch",
+    }
+}
+
+fn hex(Color([r, g, b, a]): Color) -> String {
+    format!("#{r:02X}{g:02X}{b:02X}{a:02X}")
+}
+
+const CORE: &str = "http://schemas.microsoft.com/3dmanufacturing/core/2015/02";
+const MATERIALS: &str =
+    "http://schemas.microsoft.com/3dmanufacturing/material/2015/02";
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml"/>
+</Types>
+"#;
+
+const RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Target="/3D/3dmodel.model" Id="rel0" Type="http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel"/>
+</Relationships>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use fj_interop::mesh::{Color, Mesh};
+    use fj_math::Point;
+
+    use crate::Unit;
+
+    #[test]
+    fn write_model() -> anyhow::Result<()> {
+        let [a, b, c, d] =
+            [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]]
+                .map(Point::from);
+
+        let mut first = Mesh::new();
+        first.push_triangle([a, b, c], Color([255, 0, 0, 255]));
+        first.push_triangle([a, c, d], Color([0, 0, 255, 128]));
+
+        let mut second = Mesh::new();
+        second.push_triangle([a, b, d], Color([0, 0, 255, 128]));
+
+        let mut model = Vec::new();
+        super::write_model(
+            &[first, Mesh::new(), second],
+            Unit::Millimeter,
+            &mut model,
+        )?;
+        let model = String::from_utf8(model)?;
+
+        assert!(model.contains(r#"<model unit="millimeter""#));
+
+        // Colors are shared between parts.
+        assert_eq!(model.matches("<m:color ").count(), 2);
+        assert!(model.contains(r##"<m:color color="#FF0000FF"/>"##));
+        assert!(model.contains(r##"<m:color color="#0000FF80"/>"##));
+
+        // Empty parts are skipped.
+        assert_eq!(model.matches("<object ").count(), 2);
+        assert_eq!(model.matches("<item ").count(), 2);
+
+        assert!(model.contains(r#"v1="0" v2="2" v3="3" pid="1" p1="1""#));
+        assert!(model.contains(r#"<object id="3" name="part-1""#));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+//! Evaluation of models in a child process
+//!
+//! A model that crashes or never returns takes down the process that it runs
+//! in. Running it in a child process protects the host application from that.
+//!
+//! The child process is another instance of the host application's executable,
+//! which is why applications need to call [`evaluate_if_child_process`] at the
+//! start of their `main` function.
+
+use std::{
+    collections::HashMap,
+    env,
+    io::{self, Read as _, Write as _},
+    path::{Path, PathBuf},
+    process::{self, Child, Command, ExitStatus, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use fj::abi::ModelResult;
+
+use crate::{call_model, Error, Parameters};
+
+/// The environment variable that passes the model library to the child process
+const LIB_PATH_VAR: &str = "FJ_HOST_MODEL_LIB";
+
+/// Separates the model's own output from the result in the child's stdout
+const RESULT_MARKER: &[u8] = b"\n--- fj-host model result ---\n";
+
+/// Evaluate the model library at `lib_path` in a child process
+pub fn evaluate(
+    lib_path: &Path,
+    arguments: &Parameters,
+    timeout: Option<Duration>,
+) -> Result<ModelResult, Error> {
+    let mut child = Command::new(env::current_exe()?)
+        .env(LIB_PATH_VAR, lib_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    // The parameters are small enough to fit into the pipe's buffer, so this
+    // can't block. Dropping `stdin` closes it, which the child waits for.
+    let stdin = child.stdin.take().expect("stdin has been configured");
+    serde_json::to_writer(stdin, &arguments.0)?;
+
+    // The output has to be read while the child is running. Otherwise the
+    // child could block on a full pipe, and never exit.
+    let mut stdout = child.stdout.take().expect("stdout has been configured");
+    let reader = thread::spawn(move || -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output)?;
+        Ok(output)
+    });
+
+    let status = wait(&mut child, timeout)?;
+    let output = reader.join().expect("Reader thread panicked")?;
+
+    let marker = output
+        .windows(RESULT_MARKER.len())
+        .rposition(|window| window == RESULT_MARKER);
+    let marker = match marker {
+        Some(marker) => marker,
+        None => {
+            io::stdout().write_all(&output)?;
+            return Err(Error::ChildProcess(status));
+        }
+    };
+
+    // Pass through whatever the model printed.
+    io::stdout().write_all(&output[..marker])?;
+
+    let result =
+        serde_json::from_slice(&output[marker + RESULT_MARKER.len()..])?;
+    Ok(result)
+}
+
+fn wait(
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> Result<ExitStatus, Error> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return Ok(child.wait()?),
+    };
+
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Err(Error::Timeout(timeout));
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Evaluate a model, if this process is a child process started by `fj-host`
+///
+/// Applications that evaluate models in a child process (see
+/// [`Evaluation::ChildProcess`]) must call this function at the start of their
+/// `main` function, before doing anything else. It returns immediately, if the
+/// current process is not such a child process. Otherwise, it evaluates the
+/// model, passes the result to the parent process, and exits.
+///
+/// [`Evaluation::ChildProcess`]: crate::Evaluation::ChildProcess
+pub fn evaluate_if_child_process() {
+    let lib_path = match env::var_os(LIB_PATH_VAR) {
+        Some(lib_path) => PathBuf::from(lib_path),
+        None => return,
+    };
+
+    if let Err(err) = evaluate_in_child(&lib_path) {
+        eprintln!("Error evaluating model: {}", err);
+        process::exit(1);
+    }
+
+    process::exit(0);
+}
+
+fn evaluate_in_child(lib_path: &Path) -> Result<(), Error> {
+    let arguments: HashMap<String, String> =
+        serde_json::from_reader(io::stdin())?;
+
+    // This is just as unsound as evaluating the model in the host process.
+    // See `call_model`.
+    let result = unsafe { call_model(lib_path, &Parameters(arguments))? };
+
+    let mut stdout = io::stdout();
+    stdout.write_all(RESULT_MARKER)?;
+    serde_json::to_writer(&mut stdout, &result)?;
+    stdout.flush()?;
+
+    Ok(())
+}
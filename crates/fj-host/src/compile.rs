@@ -0,0 +1,196 @@
+//! Compiling models, and collecting the compiler's diagnostics
+
+use std::{
+    fmt,
+    io::{self, BufReader, Read},
+    path::PathBuf,
+    process::{Child, Command, ExitStatus, Stdio},
+    thread::{self, JoinHandle},
+};
+
+use cargo_metadata::{diagnostic::DiagnosticLevel, Message};
+
+use crate::Error;
+
+/// An error reported by the compiler, while compiling a model
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// The error message
+    pub message: String,
+
+    /// The file that the error refers to, if any
+    ///
+    /// The path is relative to the root directory of the model's workspace.
+    pub file: Option<PathBuf>,
+
+    /// The line that the error refers to, starting at 1
+    pub line: Option<usize>,
+
+    /// The column that the error refers to, starting at 1
+    pub column: Option<usize>,
+
+    /// The error, as the compiler would print it
+    ///
+    /// Includes the affected source code, and any notes and suggestions.
+    pub rendered: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}", file.display())?;
+            if let (Some(line), Some(column)) = (self.line, self.column) {
+                write!(f, ":{}:{}", line, column)?;
+            }
+            write!(f, ": ")?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A running invocation of `cargo build`
+pub struct Compilation {
+    child: Child,
+    diagnostics: Option<JoinHandle<Vec<Diagnostic>>>,
+}
+
+impl Compilation {
+    /// Start compiling, using the provided `cargo build` command
+    pub fn start(mut command: Command) -> io::Result<Self> {
+        // Cargo's own output, like its progress, still goes to stderr.
+        let mut child = command
+            .arg("--message-format=json")
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        // The output has to be read while Cargo is running. Otherwise Cargo
+        // could block on a full pipe, and never exit.
+        let stdout = child.stdout.take().expect("stdout has been configured");
+        let diagnostics = thread::spawn(move || read_diagnostics(stdout));
+
+        Ok(Self {
+            child,
+            diagnostics: Some(diagnostics),
+        })
+    }
+
+    /// Wait for the compilation to finish
+    pub fn wait(&mut self) -> Result<(), Error> {
+        let status = self.child.wait();
+        self.finish(status)
+    }
+
+    /// Check whether the compilation has finished, without blocking
+    ///
+    /// Returns `None`, if it is still running.
+    pub fn try_wait(&mut self) -> Option<Result<(), Error>> {
+        match self.child.try_wait() {
+            Ok(None) => None,
+            Ok(Some(status)) => Some(self.finish(Ok(status))),
+            Err(err) => Some(self.finish(Err(err))),
+        }
+    }
+
+    /// Stop the compilation
+    pub fn kill(&mut self) {
+        // Killing Cargo doesn't necessarily stop the compiler processes it has
+        // started right away. But it releases the lock on the build directory,
+        // so the next build can start.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    fn finish(&mut self, status: io::Result<ExitStatus>) -> Result<(), Error> {
+        let status = status?;
+        let diagnostics = self
+            .diagnostics
+            .take()
+            .map(|diagnostics| {
+                diagnostics
+                    .join()
+                    .expect("Thread reading diagnostics panicked")
+            })
+            .unwrap_or_default();
+
+        if !status.success() {
+            return Err(Error::Compile { diagnostics });
+        }
+
+        Ok(())
+    }
+}
+
+fn read_diagnostics(stdout: impl Read) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for message in Message::parse_stream(BufReader::new(stdout)) {
+        let message = match message {
+            Ok(Message::CompilerMessage(message)) => message.message,
+            Ok(_) => continue,
+            Err(_) => break,
+        };
+
+        if !matches!(
+            message.level,
+            DiagnosticLevel::Error | DiagnosticLevel::Ice
+        ) {
+            continue;
+        }
+
+        // The compiler finishes with a summary of the number of errors, which
+        // doesn't add anything to the list.
+        let span = message.spans.iter().find(|span| span.is_primary);
+        if span.is_none() && message.message.starts_with("aborting due to") {
+            continue;
+        }
+
+        diagnostics.push(Diagnostic {
+            message: message.message.clone(),
+            file: span.map(|span| PathBuf::from(&span.file_name)),
+            line: span.map(|span| span.line_start),
+            column: span.map(|span| span.column_start),
+            rendered: message.rendered.clone().unwrap_or_default(),
+        });
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{read_diagnostics, Diagnostic};
+
+    // Captured from `cargo build --message-format=json`, for a model with one
+    // error and one warning.
+    const MESSAGES: &str = r#"{"reason":"compiler-message","package_id":"cuboid 0.1.0 (path+file:///home/user/cuboid)","manifest_path":"/home/user/cuboid/Cargo.toml","target":{"kind":["cdylib"],"crate_types":["cdylib"],"name":"cuboid","src_path":"/home/user/cuboid/src/lib.rs","edition":"2021","doc":true,"doctest":false,"test":true},"message":{"rendered":"warning: unused variable: `y`\n --> src/lib.rs:8:9\n  |\n8 |     let y = 1.;\n  |         ^ help: if this is intentional, prefix it with an underscore: `_y`\n  |\n  = note: `#[warn(unused_variables)]` on by default\n\n","children":[{"children":[],"code":null,"level":"note","message":"`#[warn(unused_variables)]` on by default","rendered":null,"spans":[]}],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `y`","spans":[{"byte_end":163,"byte_start":162,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":null,"line_end":8,"line_start":8,"suggested_replacement":null,"suggestion_applicability":null,"text":[{"highlight_end":10,"highlight_start":9,"text":"    let y = 1.;"}]}]}}
+{"reason":"compiler-message","package_id":"cuboid 0.1.0 (path+file:///home/user/cuboid)","manifest_path":"/home/user/cuboid/Cargo.toml","target":{"kind":["cdylib"],"crate_types":["cdylib"],"name":"cuboid","src_path":"/home/user/cuboid/src/lib.rs","edition":"2021","doc":true,"doctest":false,"test":true},"message":{"rendered":"error[E0425]: cannot find value `x` in this scope\n --> src/lib.rs:9:5\n  |\n9 |     x\n  |     ^ not found in this scope\n\n","children":[],"code":{"code":"E0425","explanation":null},"level":"error","message":"cannot find value `x` in this scope","spans":[{"byte_end":180,"byte_start":179,"column_end":6,"column_start":5,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":"not found in this scope","line_end":9,"line_start":9,"suggested_replacement":null,"suggestion_applicability":null,"text":[{"highlight_end":6,"highlight_start":5,"text":"    x"}]}]}}
+{"reason":"compiler-message","package_id":"cuboid 0.1.0 (path+file:///home/user/cuboid)","manifest_path":"/home/user/cuboid/Cargo.toml","target":{"kind":["cdylib"],"crate_types":["cdylib"],"name":"cuboid","src_path":"/home/user/cuboid/src/lib.rs","edition":"2021","doc":true,"doctest":false,"test":true},"message":{"rendered":"error: aborting due to previous error; 1 warning emitted\n\n","children":[],"code":null,"level":"error","message":"aborting due to previous error; 1 warning emitted","spans":[]}}
+{"reason":"build-finished","success":false}
+"#;
+
+    #[test]
+    fn read_diagnostics_from_json() {
+        let diagnostics = read_diagnostics(MESSAGES.as_bytes());
+
+        let rendered = "error[E0425]: cannot find value `x` in this scope\n \
+            --> src/lib.rs:9:5\n  |\n9 |     x\n  |     ^ not found in this \
+            scope\n\n";
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic {
+                message: "cannot find value `x` in this scope".to_owned(),
+                file: Some(PathBuf::from("src/lib.rs")),
+                line: Some(9),
+                column: Some(5),
+                rendered: rendered.to_owned(),
+            }]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "src/lib.rs:9:5: cannot find value `x` in this scope"
+        );
+    }
+}
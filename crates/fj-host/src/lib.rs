@@ -15,29 +15,47 @@
 
 #![warn(missing_docs)]
 
+mod child_process;
+mod compile;
 mod platform;
+mod watch_set;
+mod watcher;
+
+pub use self::{
+    child_process::evaluate_if_child_process,
+    compile::Diagnostic,
+    watch_set::DEFAULT_IGNORED_PATHS,
+    watcher::{Event, Watcher},
+};
 
 use std::{
-    collections::{HashMap, HashSet},
-    ffi::OsStr,
+    collections::HashMap,
     io,
     ops::{Deref, DerefMut},
     path::{Path, PathBuf},
-    process::Command,
+    process::{Command, ExitStatus},
     sync::mpsc,
-    thread,
+    time::Duration,
 };
 
+use fj::abi::ModelResult;
 use notify::Watcher as _;
 use thiserror::Error;
 
-use self::platform::HostPlatform;
+use self::{
+    compile::Compilation, platform::HostPlatform, watch_set::WatchSet,
+    watcher::Message,
+};
 
 /// Represents a Fornjot model
 pub struct Model {
-    src_path: PathBuf,
-    lib_path: PathBuf,
+    watch_set: WatchSet,
+    ignored_paths: Vec<String>,
+    lib_file: String,
     manifest_path: PathBuf,
+    default_target_dir: PathBuf,
+    build_options: BuildOptions,
+    evaluation: Evaluation,
 }
 
 impl Model {
@@ -45,8 +63,8 @@ impl Model {
     /// containing `Cargo.toml`).
     ///
     /// Optionally, the target directory where plugin files are compiled to can
-    /// be provided. If it is not provided, the target directory is assumed to
-    /// be located within the model path.
+    /// be provided, using [`Model::with_build_options`]. If it is not provided,
+    /// the target directory that Cargo would use is assumed.
     pub fn from_path(path: PathBuf) -> Result<Self, Error> {
         let crate_dir = path.canonicalize()?;
 
@@ -55,23 +73,70 @@ impl Model {
             .exec()?;
 
         let pkg = package_associated_with_directory(&metadata, &crate_dir)?;
-        let src_path = crate_dir.join("src");
+        let watch_set = WatchSet::new(&metadata, pkg);
 
-        let lib_path = {
+        let lib_file = {
             let name = pkg.name.replace('-', "_");
-            let file = HostPlatform::lib_file_name(&name);
-            let target_dir =
-                metadata.target_directory.clone().into_std_path_buf();
-            target_dir.join("debug").join(file)
+            HostPlatform::lib_file_name(&name)
         };
 
         Ok(Self {
-            src_path,
-            lib_path,
+            watch_set,
+            ignored_paths: DEFAULT_IGNORED_PATHS
+                .iter()
+                .map(|&pattern| pattern.to_owned())
+                .collect(),
+            lib_file,
             manifest_path: pkg.manifest_path.as_std_path().to_path_buf(),
+            default_target_dir: metadata
+                .target_directory
+                .clone()
+                .into_std_path_buf(),
+            build_options: BuildOptions::default(),
+            evaluation: Evaluation::InProcess,
         })
     }
 
+    /// Define how the model is built
+    ///
+    /// By default, the model is built using the `dev` profile, with its default
+    /// features.
+    pub fn with_build_options(mut self, build_options: BuildOptions) -> Self {
+        self.build_options = build_options;
+        self
+    }
+
+    /// Define which paths are ignored when watching the model for changes
+    ///
+    /// Expects a list of glob patterns (e.g. `*.swp`), which are matched
+    /// against the absolute path of each changed file. Replaces the default
+    /// list, [`DEFAULT_IGNORED_PATHS`].
+    pub fn with_ignored_paths(mut self, ignored_paths: Vec<String>) -> Self {
+        self.ignored_paths = ignored_paths;
+        self
+    }
+
+    /// Define how the model is evaluated
+    ///
+    /// The model is evaluated in the host process by default.
+    pub fn with_evaluation(mut self, evaluation: Evaluation) -> Self {
+        self.evaluation = evaluation;
+        self
+    }
+
+    /// The path of the dynamic library that the model is compiled to
+    fn lib_path(&self) -> PathBuf {
+        let target_dir = self
+            .build_options
+            .target_dir
+            .as_ref()
+            .unwrap_or(&self.default_target_dir);
+
+        target_dir
+            .join(self.build_options.profile_dir())
+            .join(&self.lib_file)
+    }
+
     /// Load the model once
     ///
     /// The passed arguments are provided to the model. Returns the shape that
@@ -83,45 +148,59 @@ impl Model {
         &self,
         arguments: &Parameters,
     ) -> Result<fj::Shape, Error> {
-        let manifest_path = self.manifest_path.display().to_string();
+        Compilation::start(self.build_command())?.wait()?;
+        self.evaluate(arguments)
+    }
 
-        let status = Command::new("cargo")
+    /// Load the model's metadata
+    ///
+    /// Builds the model, if necessary. The metadata describes the model and
+    /// the parameters it accepts.
+    pub fn load_metadata(&self) -> Result<fj::ModelMetadata, Error> {
+        Compilation::start(self.build_command())?.wait()?;
+        self.metadata()
+    }
+
+    /// Load the model's metadata, which must already have been built
+    fn metadata(&self) -> Result<fj::ModelMetadata, Error> {
+        // Calling the metadata function doesn't run any code from the model
+        // itself, so it isn't evaluated in a child process.
+        unsafe { call_metadata(&self.lib_path()) }
+    }
+
+    fn build_command(&self) -> Command {
+        let mut command = Command::new("cargo");
+        command
             .arg("build")
-            .args(["--manifest-path", &manifest_path])
-            .status()?;
+            .arg("--manifest-path")
+            .arg(&self.manifest_path);
+        self.build_options.apply(&mut command);
 
-        if !status.success() {
-            return Err(Error::Compile);
-        }
+        command
+    }
 
-        // So, strictly speaking this is all unsound:
-        // - `Library::new` requires us to abide by the arbitrary requirements
-        //   of any library initialization or termination routines.
-        // - `Library::get` requires us to specify the correct type for the
-        //   model function.
-        // - The model function itself is `unsafe`, because it is a function
-        //   from across an FFI interface.
-        //
-        // Typical models won't have initialization or termination routines (I
-        // think), should abide by the `ModelFn` signature, and might not do
-        // anything unsafe. But we have no way to know that the library the user
-        // told us to load actually does (I think).
-        //
-        // I don't know of a way to fix this. We should take this as motivation
-        // to switch to a better technique:
-        // https://github.com/hannobraun/Fornjot/issues/71
-        let shape = unsafe {
-            let lib = libloading::Library::new(&self.lib_path)?;
-            let model: libloading::Symbol<ModelFn> = lib.get(b"model")?;
-            model(arguments)
+    /// Evaluate the model, which must already have been built
+    fn evaluate(&self, arguments: &Parameters) -> Result<fj::Shape, Error> {
+        let lib_path = self.lib_path();
+        let result = match self.evaluation {
+            Evaluation::InProcess => unsafe {
+                call_model(&lib_path, arguments)?
+            },
+            Evaluation::ChildProcess { timeout } => {
+                child_process::evaluate(&lib_path, arguments, timeout)?
+            }
         };
 
-        Ok(shape)
+        match result {
+            ModelResult::Shape(shape) => Ok(shape),
+            ModelResult::Panic(message) => Err(Error::Panic(message.into())),
+        }
     }
 
     /// Load the model, then watch it for changes
     ///
-    /// Whenever a change is detected, the model is being reloaded.
+    /// Whenever a change is detected, the model is being reloaded. Building and
+    /// reloading the model happens on a background thread.
     ///
     /// Consumes this instance of `Model` and returns a [`Watcher`], which can
     /// be queried for changes to the model.
@@ -129,22 +208,35 @@ impl Model {
         self,
         parameters: Parameters,
     ) -> Result<Watcher, Error> {
-        let (tx, rx) = mpsc::sync_channel(0);
-        let tx2 = tx.clone();
+        let (tx, rx) = mpsc::channel();
+        let sender = tx.clone();
 
-        let watch_path = self.src_path.clone();
+        let watch_set = self.watch_set.clone();
+        let ignored_paths = watch_set::ignored_paths(&self.ignored_paths)?;
 
         let mut watcher = notify::recommended_watcher(
             move |event: notify::Result<notify::Event>| {
-                // Unfortunately the `notify` documentation doesn't say when
-                // this might happen, so no idea if it needs to be handled.
-                let event = event.expect("Error handling watch event");
+                let event = match event {
+                    Ok(event) => event,
+                    Err(err) => {
+                        // If the other end is disconnected, the `Watcher` has
+                        // been dropped. Nobody is left to report the error to.
+                        let _ = tx.send(Message::Change(Err(err.into())));
+                        return;
+                    }
+                };
 
-                // Various acceptable ModifyKind kinds. Varies across platforms
-                // (e.g. MacOs vs. Windows10)
-                if let notify::EventKind::Modify(
+                // Various acceptable kinds of events. Varies across platforms
+                // (e.g. MacOs vs. Windows10). Creating and renaming files are
+                // included, as many editors save a file by writing a new one,
+                // then renaming it.
+                if let notify::EventKind::Create(_)
+                | notify::EventKind::Modify(
                     notify::event::ModifyKind::Any,
                 )
+                | notify::EventKind::Modify(
+                    notify::event::ModifyKind::Name(_),
+                )
                 | notify::EventKind::Modify(
                     notify::event::ModifyKind::Data(
                         notify::event::DataChange::Any,
@@ -156,51 +248,88 @@ impl Model {
                     ),
                 ) = event.kind
                 {
-                    let file_ext = event
+                    // Individual files are watched through their parent
+                    // directories, so not every event is relevant.
+                    let is_relevant = event
                         .paths
-                        .get(0)
-                        .expect("File path missing in watch event")
-                        .extension();
-
-                    let black_list = HashSet::from([
-                        OsStr::new("swp"),
-                        OsStr::new("tmp"),
-                        OsStr::new("swx"),
-                    ]);
-
-                    if let Some(ext) = file_ext {
-                        if black_list.contains(ext) {
-                            return;
-                        }
+                        .iter()
+                        .any(|path| watch_set.contains(path, &ignored_paths));
+                    if !is_relevant {
+                        return;
                     }
 
-                    // This will panic, if the other end is disconnected, which
-                    // is probably the result of a panic on that thread, or the
-                    // application is being shut down.
-                    //
-                    // Either way, not much we can do about it here.
-                    tx.send(()).expect("Channel is disconnected");
+                    // If the other end is disconnected, the `Watcher` has been
+                    // dropped, and nobody is interested in the change anymore.
+                    let _ = tx.send(Message::Change(Ok(())));
                 }
             },
         )?;
 
-        watcher.watch(&watch_path, notify::RecursiveMode::Recursive)?;
-
-        // To prevent a race condition between the initial load and the start of
-        // watching, we'll trigger the initial load here, after having started
-        // watching.
-        //
-        // Will panic, if the receiving end has panicked. Not much we can do
-        // about that, if it happened.
-        thread::spawn(move || tx2.send(()).expect("Channel is disconnected"));
+        self.watch_set.watch(&mut watcher)?;
 
-        Ok(Watcher {
-            _watcher: Box::new(watcher),
-            channel: rx,
-            model: self,
+        Ok(Watcher::new(
+            Box::new(watcher),
+            sender,
+            rx,
+            self,
             parameters,
-        })
+        ))
+    }
+}
+
+/// Load the model library and call its model function
+unsafe fn call_model(
+    lib_path: &Path,
+    arguments: &Parameters,
+) -> Result<ModelResult, Error> {
+    let lib = load_library(lib_path)?;
+    let model: libloading::Symbol<ModelFn> = lib.get(b"model")?;
+    Ok(model(arguments))
+}
+
+/// Load the model library and call its metadata function
+unsafe fn call_metadata(lib_path: &Path) -> Result<fj::ModelMetadata, Error> {
+    let lib = load_library(lib_path)?;
+    let metadata: libloading::Symbol<MetadataFn> =
+        lib.get(b"model_metadata")?;
+    Ok(metadata().into())
+}
+
+/// Load the model library, making sure it is compatible with the host
+unsafe fn load_library(lib_path: &Path) -> Result<libloading::Library, Error> {
+    // So, strictly speaking this is all unsound:
+    // - `Library::new` requires us to abide by the arbitrary requirements of
+    //   any library initialization or termination routines.
+    // - `Library::get` requires us to specify the correct type for the model
+    //   function.
+    // - The model function itself is `unsafe`, because it is a function from
+    //   across an FFI interface.
+    //
+    // Typical models won't have initialization or termination routines (I
+    // think), should abide by the `ModelFn` signature, and might not do
+    // anything unsafe. But we have no way to know that the library the user
+    // told us to load actually does (I think).
+    //
+    // I don't know of a way to fix this. We should take this as motivation to
+    // switch to a better technique:
+    // https://github.com/hannobraun/Fornjot/issues/71
+    let lib = libloading::Library::new(lib_path)?;
+
+    // Models compiled against a version of `fj` that predates the version
+    // check don't export `fj_version`.
+    let model_version = lib
+        .get::<VersionFn>(b"fj_version")
+        .ok()
+        .map(|version| version().as_str().to_owned());
+    let host_version = fj::abi::Version::current().as_str();
+    if model_version.as_deref() != Some(host_version) {
+        return Err(Error::IncompatibleVersion {
+            host: host_version.to_owned(),
+            model: model_version,
+        });
     }
+
+    Ok(lib)
 }
 
 fn package_associated_with_directory<'m>(
@@ -247,52 +376,68 @@ fn ambiguous_path_error(
     }
 }
 
-/// Watches a model for changes, reloading it continually
-pub struct Watcher {
-    _watcher: Box<dyn notify::Watcher>,
-    channel: mpsc::Receiver<()>,
-    model: Model,
-    parameters: Parameters,
-}
+/// Options for building a model
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BuildOptions {
+    /// The Cargo profile to build with (e.g. `release`)
+    ///
+    /// The `dev` profile is used, if this is `None`.
+    pub profile: Option<String>,
+
+    /// Cargo features of the model to enable
+    pub features: Vec<String>,
 
-impl Watcher {
-    /// Receive an updated shape that the reloaded model created
+    /// The directory that the model is compiled to
     ///
-    /// Returns `None`, if the model has not changed since the last time this
-    /// method was called.
-    pub fn receive(&self) -> Option<fj::Shape> {
-        match self.channel.try_recv() {
-            Ok(()) => {
-                let shape = match self.model.load_once(&self.parameters) {
-                    Ok(shape) => shape,
-                    Err(Error::Compile) => {
-                        // It would be better to display an error in the UI,
-                        // where the user can actually see it. Issue:
-                        // https://github.com/hannobraun/fornjot/issues/30
-                        println!("Error compiling model");
-                        return None;
-                    }
-                    Err(err) => {
-                        panic!("Error reloading model: {:?}", err);
-                    }
-                };
+    /// The directory that Cargo would use otherwise is used, if this is `None`.
+    pub target_dir: Option<PathBuf>,
+}
 
-                Some(shape)
-            }
-            Err(mpsc::TryRecvError::Empty) => {
-                // Nothing to receive from the channel.
-                None
-            }
-            Err(mpsc::TryRecvError::Disconnected) => {
-                // The other end has disconnected. This is probably the result
-                // of a panic on the other thread, or a program shutdown in
-                // progress. In any case, not much we can do here.
-                panic!();
-            }
+impl BuildOptions {
+    fn apply(&self, command: &mut Command) {
+        if let Some(profile) = &self.profile {
+            command.args(["--profile", profile]);
+        }
+        if !self.features.is_empty() {
+            command.args(["--features", &self.features.join(",")]);
+        }
+        if let Some(target_dir) = &self.target_dir {
+            command.arg("--target-dir").arg(target_dir);
+        }
+    }
+
+    /// The directory within the target directory that the profile builds to
+    fn profile_dir(&self) -> &str {
+        // Cargo places the output of its built-in profiles into directories
+        // that don't match their names. Custom profiles use their own name.
+        match self.profile.as_deref() {
+            None | Some("dev") | Some("test") => "debug",
+            Some("bench") => "release",
+            Some(profile) => profile,
         }
     }
 }
 
+/// Defines how a model is evaluated
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Evaluation {
+    /// Load the model library into the host process, and call it there
+    ///
+    /// This is the fastest option, but a model that crashes or never returns
+    /// takes the host application down with it.
+    InProcess,
+
+    /// Evaluate the model in a child process
+    ///
+    /// The shape is passed back to the host process in serialized form. The
+    /// host application must call [`evaluate_if_child_process`] at the start
+    /// of its `main` function, for this to work.
+    ChildProcess {
+        /// Stop the evaluation, if it hasn't finished after this time
+        timeout: Option<Duration>,
+    },
+}
+
 /// Parameters that are passed to a model.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Parameters(pub HashMap<String, String>);
@@ -334,20 +479,76 @@ impl DerefMut for Parameters {
 pub enum Error {
     /// Model failed to compile
     #[error("Error compiling model")]
-    Compile,
+    Compile {
+        /// The errors that the compiler reported
+        ///
+        /// Might be empty, if the error was reported by Cargo instead of the
+        /// compiler. Cargo prints those errors itself.
+        diagnostics: Vec<Diagnostic>,
+    },
 
     /// I/O error while loading the model
     #[error("I/O error while loading model")]
     Io(#[from] io::Error),
 
+    /// The model panicked
+    ///
+    /// Contains the panic message. Models panic, for example, if they are
+    /// passed invalid parameters.
+    #[error("Model panicked: {0}")]
+    Panic(String),
+
+    /// The model was compiled against an incompatible version of `fj`, or
+    /// using a different compiler
+    #[error(
+        "Model was compiled against `fj` {}, but the host uses `fj` {host}. \
+        Please update the model's dependency on `fj`, and build the model \
+        with the same Rust toolchain as the host application.",
+        model.as_deref().unwrap_or("(unknown version)")
+    )]
+    IncompatibleVersion {
+        /// The versions of `fj` and the compiler that the host was compiled
+        /// with
+        host: String,
+
+        /// The versions of `fj` and the compiler that the model was compiled
+        /// with
+        ///
+        /// `None`, if the model doesn't export its version.
+        model: Option<String>,
+    },
+
+    /// The model process did not return a result
+    ///
+    /// This is the case, if the model crashed while being evaluated in a child
+    /// process.
+    #[error("Model process exited without a result ({0})")]
+    ChildProcess(ExitStatus),
+
+    /// The model did not finish in time, when evaluated in a child process
+    #[error("Model evaluation timed out after {0:?}")]
+    Timeout(Duration),
+
+    /// Error passing data to or from the child process that evaluates the model
+    #[error("Error communicating with model process")]
+    Serialization(#[from] serde_json::Error),
+
     /// Failed to load the model's dynamic library
     #[error("Error loading model from dynamic library")]
     LibLoading(#[from] libloading::Error),
 
+    /// Invalid pattern in the list of ignored paths
+    #[error("Invalid pattern for ignored paths")]
+    InvalidGlob(#[from] globset::Error),
+
     /// Error while watching the model code for changes
     #[error("Error watching model for changes")]
     Notify(#[from] notify::Error),
 
+    /// The thread that watches the model for changes has stopped
+    #[error("Stopped watching model for changes")]
+    WatcherDisconnected,
+
     /// An error occurred while trying to use evaluate
     /// [`cargo_metadata::MetadataCommand`].
     #[error("Unable to determine the crate's metadata")]
@@ -371,4 +572,29 @@ pub enum Error {
     },
 }
 
-type ModelFn = unsafe extern "C" fn(args: &Parameters) -> fj::Shape;
+type ModelFn = unsafe extern "C" fn(args: &Parameters) -> ModelResult;
+type VersionFn = unsafe extern "C" fn() -> fj::abi::Version;
+type MetadataFn = unsafe extern "C" fn() -> fj::abi::ModelMetadata;
+
+#[cfg(test)]
+mod tests {
+    use super::BuildOptions;
+
+    #[test]
+    fn profile_dir() {
+        let profile_dir = |profile: Option<&str>| {
+            let options = BuildOptions {
+                profile: profile.map(str::to_owned),
+                ..BuildOptions::default()
+            };
+            options.profile_dir().to_owned()
+        };
+
+        assert_eq!(profile_dir(None), "debug");
+        assert_eq!(profile_dir(Some("dev")), "debug");
+        assert_eq!(profile_dir(Some("test")), "debug");
+        assert_eq!(profile_dir(Some("release")), "release");
+        assert_eq!(profile_dir(Some("bench")), "release");
+        assert_eq!(profile_dir(Some("fast-debug")), "fast-debug");
+    }
+}
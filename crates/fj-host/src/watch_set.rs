@@ -0,0 +1,208 @@
+//! The set of files that a model depends on
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::{Path, PathBuf},
+};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::Error;
+
+/// Paths that are ignored by default
+///
+/// These are temporary files that editors create next to the edited file.
+pub const DEFAULT_IGNORED_PATHS: &[&str] = &["*.swp", "*.swx", "*.tmp"];
+
+/// The files that a model depends on
+///
+/// Consists of the sources, manifests, and build scripts of the model's crate,
+/// and of all its local dependencies (i.e. all dependencies that are neither
+/// from a registry, nor from a Git repository).
+#[derive(Clone, Debug)]
+pub struct WatchSet {
+    /// Directories whose content is watched recursively
+    directories: BTreeSet<PathBuf>,
+
+    /// Individual files that are watched
+    files: BTreeSet<PathBuf>,
+}
+
+impl WatchSet {
+    pub fn new(
+        metadata: &cargo_metadata::Metadata,
+        package: &cargo_metadata::Package,
+    ) -> Self {
+        let mut watch_set = Self {
+            directories: BTreeSet::new(),
+            files: BTreeSet::new(),
+        };
+
+        // A workspace manifest can define dependencies and profiles for all of
+        // the workspace's packages.
+        let workspace_manifest = metadata.workspace_root.join("Cargo.toml");
+        watch_set
+            .files
+            .insert(workspace_manifest.into_std_path_buf());
+
+        for package in local_packages(metadata, package) {
+            watch_set.add_package(package);
+        }
+
+        watch_set
+    }
+
+    fn add_package(&mut self, package: &cargo_metadata::Package) {
+        self.files
+            .insert(package.manifest_path.clone().into_std_path_buf());
+
+        for target in &package.targets {
+            let src_path = target.src_path.as_std_path();
+
+            let is_build_script =
+                target.kind.iter().any(|kind| kind == "custom-build");
+            let is_library = target.kind.iter().any(|kind| {
+                matches!(
+                    kind.as_str(),
+                    "lib"
+                        | "rlib"
+                        | "dylib"
+                        | "cdylib"
+                        | "staticlib"
+                        | "proc-macro"
+                )
+            });
+
+            if is_build_script {
+                // The build script is usually located in the root directory of
+                // the crate. Watching that recursively would include the
+                // target directory.
+                self.files.insert(src_path.to_path_buf());
+            } else if is_library {
+                if let Some(dir) = src_path.parent() {
+                    self.directories.insert(dir.to_path_buf());
+                }
+            }
+        }
+    }
+
+    /// Start watching all paths in the set
+    ///
+    /// Individual files are watched through their parent directories, as many
+    /// editors replace a file when saving it, instead of modifying it.
+    pub fn watch(
+        &self,
+        watcher: &mut dyn notify::Watcher,
+    ) -> Result<(), Error> {
+        for dir in &self.directories {
+            if dir.exists() {
+                watcher.watch(dir, notify::RecursiveMode::Recursive)?;
+            }
+        }
+
+        let parents = self
+            .files
+            .iter()
+            .filter_map(|file| file.parent())
+            .filter(|parent| parent.exists())
+            .collect::<HashSet<_>>();
+        for parent in parents {
+            watcher.watch(parent, notify::RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(())
+    }
+
+    /// Indicate whether a change to the given path affects the model
+    pub fn contains(&self, path: &Path, ignored: &GlobSet) -> bool {
+        if ignored.is_match(path) {
+            return false;
+        }
+
+        self.files.contains(path)
+            || self.directories.iter().any(|dir| path.starts_with(dir))
+    }
+}
+
+/// Build a glob set from the given patterns
+pub fn ignored_paths(patterns: &[String]) -> Result<GlobSet, Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Find the given package and all its local dependencies
+fn local_packages<'m>(
+    metadata: &'m cargo_metadata::Metadata,
+    package: &'m cargo_metadata::Package,
+) -> Vec<&'m cargo_metadata::Package> {
+    let resolve = match &metadata.resolve {
+        Some(resolve) => resolve,
+        None => return vec![package],
+    };
+
+    let mut packages = Vec::new();
+    let mut visited = HashSet::new();
+    let mut to_visit = vec![&package.id];
+
+    while let Some(id) = to_visit.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+
+        // Packages from a registry or a Git repository have a source. Those
+        // can't be edited locally, and don't need to be watched.
+        let package = &metadata[id];
+        if package.source.is_some() {
+            continue;
+        }
+        packages.push(package);
+
+        if let Some(node) = resolve.nodes.iter().find(|node| &node.id == id) {
+            to_visit.extend(&node.dependencies);
+        }
+    }
+
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{ignored_paths, WatchSet, DEFAULT_IGNORED_PATHS};
+
+    #[test]
+    fn contains() {
+        let watch_set = WatchSet {
+            directories: [PathBuf::from("/model/src")].into(),
+            files: [
+                PathBuf::from("/model/Cargo.toml"),
+                PathBuf::from("/model/build.rs"),
+            ]
+            .into(),
+        };
+        let ignored = ignored_paths(
+            &DEFAULT_IGNORED_PATHS
+                .iter()
+                .map(|&pattern| pattern.to_owned())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        let contains =
+            |path: &str| watch_set.contains(Path::new(path), &ignored);
+
+        assert!(contains("/model/Cargo.toml"));
+        assert!(contains("/model/build.rs"));
+        assert!(contains("/model/src/lib.rs"));
+        assert!(contains("/model/src/parts/wheel.rs"));
+
+        assert!(!contains("/model/Cargo.lock"));
+        assert!(!contains("/model/target/debug/libmodel.so"));
+        assert!(!contains("/model/src/.lib.rs.swp"));
+    }
+}
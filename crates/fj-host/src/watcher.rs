@@ -0,0 +1,327 @@
+//! Watching a model for changes, and reloading it in the background
+
+use std::{sync::mpsc, thread, time::Duration};
+
+use crate::{compile::Compilation, Diagnostic, Error, Model, Parameters};
+
+/// How long to wait for further changes, before starting a build
+///
+/// Editors might write a file in several steps, and saving multiple files
+/// results in a burst of changes. This makes sure that a burst of changes only
+/// triggers a single build.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// How often a running build checks whether it has been superseded
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Watches a model for changes, reloading it continually
+///
+/// The model is built and evaluated on a background thread. Use
+/// [`Watcher::receive`] to get notified of the progress.
+pub struct Watcher {
+    _watcher: Box<dyn notify::Watcher>,
+    messages: mpsc::Sender<Message>,
+    events: mpsc::Receiver<Event>,
+    parameters: Parameters,
+    disconnected: bool,
+}
+
+impl Watcher {
+    pub(crate) fn new(
+        watcher: Box<dyn notify::Watcher>,
+        sender: mpsc::Sender<Message>,
+        receiver: mpsc::Receiver<Message>,
+        model: Model,
+        parameters: Parameters,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+
+        let worker_parameters = parameters.clone();
+        thread::spawn(move || {
+            // The worker only stops, once this `Watcher` has been dropped.
+            // Nothing left to do then.
+            let _ = work(&model, worker_parameters, &receiver, &tx);
+        });
+
+        Self {
+            _watcher: watcher,
+            messages: sender,
+            events: rx,
+            parameters,
+            disconnected: false,
+        }
+    }
+
+    /// The parameters that the model is evaluated with
+    pub fn parameters(&self) -> &Parameters {
+        &self.parameters
+    }
+
+    /// Evaluate the model again, using new parameters
+    ///
+    /// The model is not rebuilt. If it currently doesn't build, the new
+    /// parameters are used, once it does again. As with changes to the model,
+    /// the result is reported through [`Watcher::receive`].
+    pub fn update_parameters(&mut self, parameters: Parameters) {
+        self.parameters = parameters.clone();
+
+        // If the other end is disconnected, the background thread has stopped.
+        // That is reported by `receive`.
+        let _ = self.messages.send(Message::Parameters(parameters));
+    }
+
+    /// Receive the next event from the watched model
+    ///
+    /// Returns `None`, if nothing has happened since the last time this method
+    /// was called. Never blocks, as building and reloading the model happens
+    /// in the background.
+    ///
+    /// Errors are returned as events, and don't stop the watching. The next
+    /// change to the model will trigger another reload.
+    pub fn receive(&mut self) -> Option<Event> {
+        if self.disconnected {
+            return None;
+        }
+
+        match self.events.try_recv() {
+            Ok(event) => Some(event),
+            Err(mpsc::TryRecvError::Empty) => {
+                // Nothing to receive from the channel.
+                None
+            }
+            Err(mpsc::TryRecvError::Disconnected) => {
+                // The background thread has stopped. This is probably the
+                // result of a panic on that thread. The model won't be
+                // reloaded anymore, which is only reported once.
+                self.disconnected = true;
+                Some(Event::WatchFailed(Error::WatcherDisconnected))
+            }
+        }
+    }
+}
+
+/// An event that is emitted by a [`Watcher`]
+#[derive(Debug)]
+pub enum Event {
+    /// The model has changed, and is being built
+    BuildStarted,
+
+    /// The model changed again while being built
+    ///
+    /// The build has been cancelled. Another build will start shortly.
+    BuildCancelled,
+
+    /// The model has been built, and its metadata has been loaded
+    ///
+    /// The metadata describes the parameters that the model accepts.
+    MetadataReady(fj::ModelMetadata),
+
+    /// The model is being evaluated
+    ///
+    /// This happens after the model has been built, or after its parameters
+    /// have been updated.
+    EvaluationStarted,
+
+    /// The model has been reloaded and produced a new shape
+    ShapeReady(fj::Shape),
+
+    /// The model has changed, but failed to compile
+    ///
+    /// Contains the errors that the compiler reported.
+    CompileFailed(Vec<Diagnostic>),
+
+    /// The model compiled, but loading it failed
+    LoadFailed(Error),
+
+    /// An error occurred while watching the model for changes
+    ///
+    /// Depending on the error, changes to the model might not be picked up
+    /// anymore.
+    WatchFailed(Error),
+}
+
+/// A message to the thread that builds and evaluates the model
+pub(crate) enum Message {
+    /// A file that the model depends on has changed
+    ///
+    /// Carries the error, if watching for changes failed.
+    Change(Result<(), Error>),
+
+    /// The model should be evaluated with new parameters
+    Parameters(Parameters),
+}
+
+/// Indicates that the [`Watcher`] has been dropped
+struct Stopped;
+
+fn work(
+    model: &Model,
+    mut parameters: Parameters,
+    messages: &mpsc::Receiver<Message>,
+    events: &mpsc::Sender<Event>,
+) -> Result<(), Stopped> {
+    // Watching has started before this thread was spawned. Triggering the
+    // initial load here, without waiting for a change, makes sure that no
+    // change between the initial load and the start of watching is missed.
+    let mut changed = true;
+    let mut parameters_updated = false;
+
+    // New parameters can only be evaluated, if the last build succeeded.
+    let mut built = false;
+
+    loop {
+        if !changed && !parameters_updated {
+            match receive(messages, events, &mut parameters, None)? {
+                Received::Change => changed = true,
+                Received::Parameters => parameters_updated = true,
+                Received::Nothing => {}
+            }
+        }
+
+        // Wait until the burst of changes is over. Parameters that are edited
+        // continuously, for example using a slider, arrive in bursts too. Only
+        // the latest ones are evaluated, but there's no need to wait for more.
+        loop {
+            let timeout = if changed { DEBOUNCE } else { Duration::ZERO };
+            match receive(messages, events, &mut parameters, Some(timeout))? {
+                Received::Change => changed = true,
+                Received::Parameters => parameters_updated = true,
+                Received::Nothing => break,
+            }
+        }
+        parameters_updated = false;
+
+        if changed {
+            changed = false;
+            built = false;
+
+            send(events, Event::BuildStarted)?;
+
+            match build(model, &mut parameters, messages, events)? {
+                Build::Finished(Ok(())) => {}
+                Build::Finished(Err(Error::Compile { diagnostics })) => {
+                    send(events, Event::CompileFailed(diagnostics))?;
+                    continue;
+                }
+                Build::Finished(Err(err)) => {
+                    send(events, Event::LoadFailed(err))?;
+                    continue;
+                }
+                Build::Superseded => {
+                    changed = true;
+                    send(events, Event::BuildCancelled)?;
+                    continue;
+                }
+            }
+
+            built = true;
+
+            let event = match model.metadata() {
+                Ok(metadata) => Event::MetadataReady(metadata),
+                Err(err) => Event::LoadFailed(err),
+            };
+            send(events, event)?;
+        } else if !built {
+            continue;
+        }
+
+        // Evaluating the model can't be cancelled. Changes that arrive in the
+        // meantime are picked up afterwards.
+        send(events, Event::EvaluationStarted)?;
+        let event = match model.evaluate(&parameters) {
+            Ok(shape) => Event::ShapeReady(shape),
+            Err(err) => Event::LoadFailed(err),
+        };
+        send(events, event)?;
+    }
+}
+
+enum Build {
+    Finished(Result<(), Error>),
+    Superseded,
+}
+
+/// Build the model, unless another change arrives before the build finishes
+///
+/// Parameters that arrive in the meantime are used for the evaluation after
+/// the build.
+fn build(
+    model: &Model,
+    parameters: &mut Parameters,
+    messages: &mpsc::Receiver<Message>,
+    events: &mpsc::Sender<Event>,
+) -> Result<Build, Stopped> {
+    let mut compilation = match Compilation::start(model.build_command()) {
+        Ok(compilation) => compilation,
+        Err(err) => return Ok(Build::Finished(Err(err.into()))),
+    };
+
+    loop {
+        if let Some(result) = compilation.try_wait() {
+            return Ok(Build::Finished(result));
+        }
+
+        match receive(messages, events, parameters, Some(POLL_INTERVAL)) {
+            Ok(Received::Nothing | Received::Parameters) => {}
+            Ok(Received::Change) => {
+                compilation.kill();
+                return Ok(Build::Superseded);
+            }
+            Err(Stopped) => {
+                compilation.kill();
+                return Err(Stopped);
+            }
+        }
+    }
+}
+
+/// What [`receive`] has received
+enum Received {
+    /// The model has changed
+    Change,
+
+    /// The parameters have been updated
+    Parameters,
+
+    /// Nothing has been received within the timeout
+    Nothing,
+}
+
+/// Wait for a change to the model, or for new parameters
+///
+/// New parameters are written to `parameters`. Waits indefinitely, if no
+/// timeout is provided. Errors that occur while watching are passed on.
+fn receive(
+    messages: &mpsc::Receiver<Message>,
+    events: &mpsc::Sender<Event>,
+    parameters: &mut Parameters,
+    timeout: Option<Duration>,
+) -> Result<Received, Stopped> {
+    loop {
+        let message = match timeout {
+            Some(timeout) => match messages.recv_timeout(timeout) {
+                Ok(message) => message,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    return Ok(Received::Nothing)
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err(Stopped)
+                }
+            },
+            None => messages.recv().map_err(|_| Stopped)?,
+        };
+
+        match message {
+            Message::Change(Ok(())) => return Ok(Received::Change),
+            Message::Change(Err(err)) => send(events, Event::WatchFailed(err))?,
+            Message::Parameters(new_parameters) => {
+                *parameters = new_parameters;
+                return Ok(Received::Parameters);
+            }
+        }
+    }
+}
+
+fn send(events: &mpsc::Sender<Event>, event: Event) -> Result<(), Stopped> {
+    events.send(event).map_err(|_| Stopped)
+}
@@ -0,0 +1,603 @@
+//! A bounding volume hierarchy over the triangles of a mesh
+
+use fj_math::{Aabb, Point, Scalar, Vector};
+
+use crate::mesh::{Mesh, Triangle};
+
+/// A bounding volume hierarchy (BVH) over the triangles of a [`Mesh`]
+///
+/// Speeds up spatial queries against a mesh, which would otherwise require a
+/// linear scan over all of its triangles. The BVH is built once from a mesh,
+/// and doesn't track any changes made to the mesh afterwards.
+///
+/// Triangles are identified by their index, which refers to their position in
+/// the iterator returned by [`Mesh::triangles`].
+#[derive(Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    entries: Vec<Entry>,
+    triangles: Vec<Triangle>,
+}
+
+impl Bvh {
+    /// The maximum number of triangles in a leaf node
+    const MAX_TRIANGLES_PER_LEAF: usize = 4;
+
+    /// Build a BVH over the triangles of the provided mesh
+    pub fn new(mesh: &Mesh<Point<3>>) -> Self {
+        let triangles: Vec<_> = mesh.triangles().collect();
+
+        let mut entries: Vec<_> = triangles
+            .iter()
+            .enumerate()
+            .map(|(index, triangle)| {
+                let aabb = triangle.inner.aabb();
+                Entry {
+                    index,
+                    aabb,
+                    centroid: aabb.center(),
+                }
+            })
+            .collect();
+
+        let mut nodes = Vec::new();
+        if !entries.is_empty() {
+            let len = entries.len();
+            build(&mut nodes, &mut entries, 0, len);
+        }
+
+        Self {
+            nodes,
+            entries,
+            triangles,
+        }
+    }
+
+    /// Indicate whether the BVH contains any triangles
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// Access the bounding box of all triangles in the BVH
+    ///
+    /// Returns `None`, if the BVH is empty.
+    pub fn aabb(&self) -> Option<Aabb<3>> {
+        self.nodes.first().map(|node| node.aabb)
+    }
+
+    /// Cast a ray against the triangles in the BVH
+    ///
+    /// Returns the hit that is closest to the ray's origin, if any. The time of
+    /// impact is given in multiples of `dir`.
+    pub fn cast_ray(
+        &self,
+        origin: Point<3>,
+        dir: Vector<3>,
+        max_toi: f64,
+    ) -> Option<RayHit> {
+        let mut closest: Option<RayHit> = None;
+        let mut stack = self.root();
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            let max_toi =
+                closest.map(|hit| hit.toi.into_f64()).unwrap_or(max_toi);
+
+            if node
+                .aabb
+                .cast_local_ray(origin, dir, max_toi, true)
+                .is_none()
+            {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Branch { left, right } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+                NodeKind::Leaf { start, end } => {
+                    for entry in &self.entries[start..end] {
+                        let toi = self.triangles[entry.index]
+                            .inner
+                            .cast_local_ray(origin, dir, max_toi, true);
+
+                        if let Some(toi) = toi {
+                            if closest.map(|hit| toi < hit.toi).unwrap_or(true)
+                            {
+                                closest = Some(RayHit {
+                                    triangle: entry.index,
+                                    toi,
+                                    point: origin + dir * toi,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        closest
+    }
+
+    /// Cast a ray against the triangles in the BVH, returning all hits
+    ///
+    /// The hits are ordered by their time of impact. Hits that have the same
+    /// time of impact (which happens, if the ray passes through an edge or a
+    /// vertex that is shared between triangles) are only returned once.
+    pub fn cast_ray_all(
+        &self,
+        origin: Point<3>,
+        dir: Vector<3>,
+        max_toi: f64,
+    ) -> Vec<RayHit> {
+        let mut hits = Vec::new();
+        let mut stack = self.root();
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+
+            if node
+                .aabb
+                .cast_local_ray(origin, dir, max_toi, true)
+                .is_none()
+            {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Branch { left, right } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+                NodeKind::Leaf { start, end } => {
+                    for entry in &self.entries[start..end] {
+                        let toi = self.triangles[entry.index]
+                            .inner
+                            .cast_local_ray(origin, dir, max_toi, true);
+
+                        if let Some(toi) = toi {
+                            hits.push(RayHit {
+                                triangle: entry.index,
+                                toi,
+                                point: origin + dir * toi,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        hits.sort_by_key(|hit| (hit.toi, hit.triangle));
+        hits.dedup_by_key(|hit| hit.toi);
+
+        hits
+    }
+
+    /// Determine whether a point is contained within the mesh
+    ///
+    /// This assumes that the mesh is closed. The result is meaningless, if it
+    /// isn't.
+    pub fn contains_point(&self, point: &Point<3>) -> bool {
+        if let Some(aabb) = self.aabb() {
+            if !aabb.contains(*point) {
+                return false;
+            }
+        }
+
+        // Cast a ray in an arbitrary direction and count how often it passes
+        // through the mesh. The direction is chosen to be somewhat irregular,
+        // to make it unlikely that the ray passes exactly through the edges or
+        // vertices of axis-aligned geometry.
+        let dir = Vector::from([0.5773, 0.5779, 0.5765]);
+        let hits = self.cast_ray_all(*point, dir, f64::INFINITY);
+
+        hits.len() % 2 == 1
+    }
+
+    /// Find all triangles whose bounding boxes overlap the provided AABB
+    ///
+    /// Returns the indices of those triangles, in ascending order.
+    pub fn triangles_overlapping(&self, aabb: &Aabb<3>) -> Vec<usize> {
+        let mut triangles = Vec::new();
+        let mut stack = self.root();
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+
+            if !node.aabb.intersects(aabb) {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Branch { left, right } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+                NodeKind::Leaf { start, end } => {
+                    for entry in &self.entries[start..end] {
+                        if entry.aabb.intersects(aabb) {
+                            triangles.push(entry.index);
+                        }
+                    }
+                }
+            }
+        }
+
+        triangles.sort_unstable();
+        triangles
+    }
+
+    /// Find the point on the mesh that is closest to the provided point
+    ///
+    /// Returns `None`, if the BVH is empty.
+    pub fn closest_point(&self, point: &Point<3>) -> Option<ClosestPoint> {
+        let mut closest: Option<ClosestPoint> = None;
+        let mut stack = self.root();
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+
+            if let Some(closest) = closest {
+                if node.aabb.distance_to_point(point) > closest.distance {
+                    continue;
+                }
+            }
+
+            match node.kind {
+                NodeKind::Branch { left, right } => {
+                    // Visit the closer child first, as that is more likely to
+                    // allow us to prune the other one.
+                    let distance_left =
+                        self.nodes[left].aabb.distance_to_point(point);
+                    let distance_right =
+                        self.nodes[right].aabb.distance_to_point(point);
+
+                    if distance_left < distance_right {
+                        stack.push(right);
+                        stack.push(left);
+                    } else {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+                NodeKind::Leaf { start, end } => {
+                    for entry in &self.entries[start..end] {
+                        let candidate = self.triangles[entry.index]
+                            .inner
+                            .closest_point(point);
+                        let distance = Point::distance(&candidate, point);
+
+                        if closest
+                            .map(|closest| distance < closest.distance)
+                            .unwrap_or(true)
+                        {
+                            closest = Some(ClosestPoint {
+                                triangle: entry.index,
+                                point: candidate,
+                                distance,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        closest
+    }
+
+    /// Find the point on the mesh that is closest to the provided triangle
+    ///
+    /// Only considers points that are closer than `max_distance`. Returns the
+    /// closest point on the provided triangle, the closest point on the mesh,
+    /// and the distance between them.
+    ///
+    /// Returns `None`, if the BVH is empty, if no point is closer than
+    /// `max_distance`, or if the triangle intersects the mesh.
+    pub fn closest_to_triangle(
+        &self,
+        triangle: &fj_math::Triangle<3>,
+        max_distance: Scalar,
+    ) -> Option<ClosestToTriangle> {
+        let aabb = triangle.aabb();
+
+        let mut closest: Option<ClosestToTriangle> = None;
+        let mut stack = self.root();
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+
+            let max_distance = closest
+                .map(|closest| closest.distance)
+                .unwrap_or(max_distance);
+            if node.aabb.distance_to_aabb(&aabb) > max_distance {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Branch { left, right } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+                NodeKind::Leaf { start, end } => {
+                    for entry in &self.entries[start..end] {
+                        let other = self.triangles[entry.index].inner;
+                        let [a, b] = triangle.closest_points(&other)?;
+                        let distance = Point::distance(&a, &b);
+
+                        if distance < max_distance
+                            && closest
+                                .map(|closest| distance < closest.distance)
+                                .unwrap_or(true)
+                        {
+                            closest = Some(ClosestToTriangle {
+                                triangle: entry.index,
+                                points: [a, b],
+                                distance,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        closest
+    }
+
+    /// Access a triangle by its index
+    ///
+    /// # Panics
+    ///
+    /// Panics, if there's no triangle with the provided index.
+    pub fn triangle(&self, index: usize) -> Triangle {
+        self.triangles[index]
+    }
+
+    /// Iterate over all triangles in the BVH
+    ///
+    /// The triangles are returned in the same order as by [`Mesh::triangles`],
+    /// meaning their indices can be determined using [`Iterator::enumerate`].
+    pub fn triangles(&self) -> impl Iterator<Item = Triangle> + '_ {
+        self.triangles.iter().copied()
+    }
+
+    fn root(&self) -> Vec<usize> {
+        if self.nodes.is_empty() {
+            Vec::new()
+        } else {
+            vec![0]
+        }
+    }
+}
+
+/// The result of casting a ray against a [`Bvh`]
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    /// The index of the triangle that was hit
+    pub triangle: usize,
+
+    /// The time of impact, in multiples of the ray's direction
+    pub toi: Scalar,
+
+    /// The point where the ray hit the triangle
+    pub point: Point<3>,
+}
+
+/// The result of a closest point query against a [`Bvh`]
+#[derive(Clone, Copy, Debug)]
+pub struct ClosestPoint {
+    /// The index of the triangle that the closest point is on
+    pub triangle: usize,
+
+    /// The closest point
+    pub point: Point<3>,
+
+    /// The distance between the closest point and the query point
+    pub distance: Scalar,
+}
+
+/// The result of a closest point query between a triangle and a [`Bvh`]
+#[derive(Clone, Copy, Debug)]
+pub struct ClosestToTriangle {
+    /// The index of the triangle in the BVH that the closest point is on
+    pub triangle: usize,
+
+    /// The closest points on the query triangle and on the BVH's triangle
+    pub points: [Point<3>; 2],
+
+    /// The distance between the closest points
+    pub distance: Scalar,
+}
+
+#[derive(Debug)]
+struct Node {
+    aabb: Aabb<3>,
+    kind: NodeKind,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum NodeKind {
+    Branch { left: usize, right: usize },
+    Leaf { start: usize, end: usize },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    index: usize,
+    aabb: Aabb<3>,
+    centroid: Point<3>,
+}
+
+/// Build the node for `entries[start..end]` and return its index
+fn build(
+    nodes: &mut Vec<Node>,
+    entries: &mut [Entry],
+    start: usize,
+    end: usize,
+) -> usize {
+    let aabb = entries[start..end]
+        .iter()
+        .map(|entry| entry.aabb)
+        .reduce(|a, b| a.merged(&b))
+        .expect("Can't build BVH node without triangles");
+
+    let index = nodes.len();
+    nodes.push(Node {
+        aabb,
+        kind: NodeKind::Leaf { start, end },
+    });
+
+    if end - start <= Bvh::MAX_TRIANGLES_PER_LEAF {
+        return index;
+    }
+
+    // Split along the axis in which the centroids are spread out the most.
+    // Splitting at the median keeps the tree balanced, regardless of how the
+    // triangles are distributed.
+    let centroids =
+        Aabb::<3>::from_points(entries[start..end].iter().map(|e| e.centroid));
+    let size = centroids.size();
+    let axis = (0..3)
+        .max_by_key(|&axis| size.components[axis])
+        .expect("Range is not empty");
+
+    entries[start..end]
+        .sort_by_key(|entry| entry.centroid.coords.components[axis]);
+
+    let mid = start + (end - start) / 2;
+    let left = build(nodes, entries, start, mid);
+    let right = build(nodes, entries, mid, end);
+
+    nodes[index].kind = NodeKind::Branch { left, right };
+
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use fj_math::{Aabb, Point, Scalar, Vector};
+
+    use crate::mesh::{Color, Mesh};
+
+    use super::Bvh;
+
+    #[test]
+    fn cast_ray() {
+        let mesh = grid(10);
+        let bvh = Bvh::new(&mesh);
+
+        let origin = Point::from([2.5, 3.25, 5.]);
+        let dir = Vector::from([0., 0., -1.]);
+
+        let hit = bvh
+            .cast_ray(origin, dir, f64::INFINITY)
+            .expect("Ray should hit mesh");
+        assert_abs_diff_eq!(
+            hit.point,
+            Point::from([2.5, 3.25, 0.]),
+            epsilon = 1e-8,
+        );
+
+        let expected = mesh
+            .triangles()
+            .position(|triangle| {
+                triangle
+                    .inner
+                    .cast_local_ray(origin, dir, f64::INFINITY, true)
+                    .is_some()
+            })
+            .expect("Brute force should find the triangle");
+        assert_eq!(hit.triangle, expected);
+
+        let miss = Point::from([20., 20., 5.]);
+        assert!(bvh.cast_ray(miss, dir, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn triangles_overlapping() {
+        let mesh = grid(10);
+        let bvh = Bvh::new(&mesh);
+
+        let aabb = Aabb::<3>::from_points([[2.2, 2.2, -1.], [3.8, 3.8, 1.]]);
+
+        let expected: Vec<_> = mesh
+            .triangles()
+            .enumerate()
+            .filter(|(_, triangle)| triangle.inner.aabb().intersects(&aabb))
+            .map(|(index, _)| index)
+            .collect();
+
+        assert_eq!(bvh.triangles_overlapping(&aabb), expected);
+    }
+
+    #[test]
+    fn closest_point() {
+        let mesh = grid(10);
+        let bvh = Bvh::new(&mesh);
+
+        let closest = bvh
+            .closest_point(&Point::from([12., 5.5, 0.]))
+            .expect("BVH is not empty");
+
+        assert_abs_diff_eq!(
+            closest.point,
+            Point::from([10., 5.5, 0.]),
+            epsilon = 1e-8,
+        );
+        assert_abs_diff_eq!(closest.distance, Scalar::from(2.), epsilon = 1e-8);
+
+        assert!(Bvh::new(&Mesh::new())
+            .closest_point(&Point::origin())
+            .is_none());
+    }
+
+    #[test]
+    fn contains_point() {
+        let mesh = Mesh::cube([0., 0., 0.], 1.);
+        let bvh = Bvh::new(&mesh);
+
+        assert!(bvh.contains_point(&Point::from([0.2, 0.3, 0.4])));
+        assert!(bvh.contains_point(&Point::from([0.1, 0.9, 0.2])));
+
+        assert!(!bvh.contains_point(&Point::from([1.5, 0.5, 0.5])));
+        assert!(!bvh.contains_point(&Point::from([-0.5, -0.5, -0.5])));
+    }
+
+    #[test]
+    fn closest_to_triangle() {
+        let bvh = Bvh::new(&grid(10));
+
+        let triangle =
+            fj_math::Triangle::from([[2., 2., 3.], [3., 2., 3.], [2., 3., 3.]]);
+
+        let closest = bvh
+            .closest_to_triangle(&triangle, Scalar::MAX)
+            .expect("Triangle doesn't intersect mesh");
+        assert_abs_diff_eq!(closest.distance, Scalar::from(3.), epsilon = 1e-8);
+
+        assert!(bvh
+            .closest_to_triangle(&triangle, Scalar::from(2.))
+            .is_none());
+    }
+
+    fn grid(n: u32) -> Mesh<Point<3>> {
+        let mut mesh = Mesh::new();
+
+        for x in 0..n {
+            for y in 0..n {
+                let [x, y] = [x, y].map(f64::from);
+
+                let a = [x, y, 0.];
+                let b = [x + 1., y, 0.];
+                let c = [x + 1., y + 1., 0.];
+                let d = [x, y + 1., 0.];
+
+                mesh.push_triangle([a, b, c], Color::default());
+                mesh.push_triangle([a, c, d], Color::default());
+            }
+        }
+
+        mesh
+    }
+}
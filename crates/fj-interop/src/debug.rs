@@ -9,6 +9,13 @@ use fj_math::{Point, Segment};
 /// Debug info from the CAD kernel that can be visualized
 #[derive(Default)]
 pub struct DebugInfo {
+    /// Whether to record triangle edge checks during triangulation
+    ///
+    /// The triangulation doesn't need triangle edge checks to produce a
+    /// correct result. They are only useful as a diagnostic, and are expensive
+    /// to compute, so they aren't recorded by default.
+    pub record_triangle_edge_checks: bool,
+
     /// Rays being used during face triangulation
     pub triangle_edge_checks: Vec<TriangleEdgeCheck>,
 }
@@ -23,10 +30,29 @@ impl DebugInfo {
     ///
     /// The resulting instance is the same, as if created by [`DebugInfo::new`],
     /// but calling `clear` might be more efficient in regard to heap
-    /// allocations.
+    /// allocations. The value of [`DebugInfo::record_triangle_edge_checks`]
+    /// is left unchanged.
     pub fn clear(&mut self) {
         self.triangle_edge_checks.clear();
     }
+
+    /// Construct an empty instance that records the same information
+    ///
+    /// This is useful for collecting debug info from work that happens in
+    /// parallel. Once that work is done, the debug info can be merged back
+    /// using [`DebugInfo::append`].
+    pub fn branch(&self) -> Self {
+        Self {
+            record_triangle_edge_checks: self.record_triangle_edge_checks,
+            ..Self::default()
+        }
+    }
+
+    /// Move all information from another instance into this one
+    pub fn append(&mut self, mut other: Self) {
+        self.triangle_edge_checks
+            .append(&mut other.triangle_edge_checks);
+    }
 }
 
 /// Record of a check to determine if a triangle edge is within a face
@@ -0,0 +1,239 @@
+//! Interference checking between shapes
+
+use std::collections::BTreeSet;
+
+use fj_math::{Aabb, Point, Scalar};
+
+use crate::{bvh::Bvh, processed_shape::ProcessedShape};
+
+/// The result of an interference check between two shapes
+///
+/// Both shapes are expected to be closed, meaning their meshes enclose a
+/// volume. The results of the interference check are meaningless otherwise.
+#[derive(Clone, Copy, Debug)]
+pub struct Interference {
+    /// The bounding box of the region where the shapes overlap
+    ///
+    /// `None`, if the shapes don't overlap. This is a conservative estimate:
+    /// The bounding box is guaranteed to contain the overlap region, but might
+    /// be larger than it.
+    pub overlap: Option<Aabb<3>>,
+
+    /// The minimum distance between the shapes
+    ///
+    /// Zero, if the shapes overlap.
+    pub distance: Scalar,
+
+    /// The pair of points that are closest to each other
+    ///
+    /// The first point is on the first shape, the second point on the second
+    /// shape. If the shapes overlap, both points are the same point within the
+    /// overlap region.
+    pub closest_points: [Point<3>; 2],
+}
+
+impl Interference {
+    /// Check the interference between two processed shapes
+    ///
+    /// Returns `None`, if either of the shapes is empty.
+    pub fn between(a: &ProcessedShape, b: &ProcessedShape) -> Option<Self> {
+        Self::between_meshes(&a.bvh, &b.bvh)
+    }
+
+    /// Check the interference between two meshes
+    ///
+    /// Returns `None`, if either of the meshes is empty.
+    pub fn between_meshes(a: &Bvh, b: &Bvh) -> Option<Self> {
+        let aabb_a = a.aabb()?;
+        let aabb_b = b.aabb()?;
+
+        if let Some(overlap) = overlap(a, b, &aabb_a, &aabb_b) {
+            return Some(overlap);
+        }
+
+        let mut closest: Option<Self> = None;
+
+        for triangle in a.triangles() {
+            let max_distance = closest
+                .map(|closest| closest.distance)
+                .unwrap_or(Scalar::MAX);
+
+            if let Some(candidate) =
+                b.closest_to_triangle(&triangle.inner, max_distance)
+            {
+                closest = Some(Self {
+                    overlap: None,
+                    distance: candidate.distance,
+                    closest_points: candidate.points,
+                });
+            }
+        }
+
+        closest
+    }
+
+    /// Indicate whether the shapes overlap
+    pub fn is_overlapping(&self) -> bool {
+        self.overlap.is_some()
+    }
+}
+
+fn overlap(
+    a: &Bvh,
+    b: &Bvh,
+    aabb_a: &Aabb<3>,
+    aabb_b: &Aabb<3>,
+) -> Option<Interference> {
+    let bounds = aabb_a.intersection(aabb_b)?;
+
+    let mut region: Option<Aabb<3>> = None;
+    let mut point_in_region = None;
+
+    let mut include = |aabb: Aabb<3>| {
+        region = Some(match region {
+            Some(region) => region.merged(&aabb),
+            None => aabb,
+        });
+    };
+
+    // Where the surfaces of the shapes cross each other, the overlap region is
+    // bounded by the triangles involved.
+    for triangle_a in a.triangles() {
+        let triangle_a = triangle_a.inner;
+        let aabb = triangle_a.aabb();
+
+        if !aabb.intersects(&bounds) {
+            continue;
+        }
+
+        for index in b.triangles_overlapping(&aabb) {
+            let triangle_b = b.triangle(index).inner;
+
+            if !triangle_a.intersects(&triangle_b) {
+                continue;
+            }
+
+            if let Some(intersection) = aabb.intersection(&triangle_b.aabb()) {
+                include(intersection);
+            }
+
+            if point_in_region.is_none() {
+                point_in_region = crossing_point(&triangle_a, &triangle_b);
+            }
+        }
+    }
+
+    // The overlap region also contains any vertex of one shape that is inside
+    // of the other. This covers the case of one shape being fully contained in
+    // the other, where the surfaces don't cross at all.
+    //
+    // Vertices are shared between triangles, so they are collected first, to
+    // check each one only once.
+    for (this, other) in [(a, b), (b, a)] {
+        let points = this
+            .triangles()
+            .flat_map(|triangle| triangle.inner.points())
+            .filter(|&point| bounds.contains(point))
+            .collect::<BTreeSet<_>>();
+
+        for point in points {
+            if other.contains_point(&point) {
+                include(Aabb {
+                    min: point,
+                    max: point,
+                });
+                point_in_region.get_or_insert(point);
+            }
+        }
+    }
+
+    let region = region?.intersection(&bounds)?;
+    let point = point_in_region.unwrap_or_else(|| region.center());
+
+    Some(Interference {
+        overlap: Some(region),
+        distance: Scalar::ZERO,
+        closest_points: [point, point],
+    })
+}
+
+/// Find a point where two intersecting triangles cross each other
+///
+/// If two triangles intersect, one of the edges of one triangle must pass
+/// through the other triangle.
+fn crossing_point(
+    a: &fj_math::Triangle<3>,
+    b: &fj_math::Triangle<3>,
+) -> Option<Point<3>> {
+    for (edges_of, other) in [(a, b), (b, a)] {
+        let [p0, p1, p2] = edges_of.points();
+
+        for [start, end] in [[p0, p1], [p1, p2], [p2, p0]] {
+            let dir = end - start;
+
+            if let Some(toi) = other.cast_local_ray(start, dir, 1., true) {
+                return Some(start + dir * toi);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use fj_math::Scalar;
+
+    use crate::{bvh::Bvh, mesh::Mesh};
+
+    use super::Interference;
+
+    #[test]
+    fn disjoint() {
+        let a = Bvh::new(&Mesh::cube([0., 0., 0.], 1.));
+        let b = Bvh::new(&Mesh::cube([3., 0., 0.], 1.));
+
+        let interference =
+            Interference::between_meshes(&a, &b).expect("Meshes not empty");
+
+        assert!(!interference.is_overlapping());
+        assert_abs_diff_eq!(
+            interference.distance,
+            Scalar::from(2.),
+            epsilon = 1e-8,
+        );
+
+        let [p, q] = interference.closest_points;
+        assert_abs_diff_eq!(p.x, Scalar::from(1.), epsilon = 1e-8);
+        assert_abs_diff_eq!(q.x, Scalar::from(3.), epsilon = 1e-8);
+    }
+
+    #[test]
+    fn crossing() {
+        let a = Bvh::new(&Mesh::cube([0., 0., 0.], 2.));
+        let b = Bvh::new(&Mesh::cube([1.5, 0.5, 0.5], 1.));
+
+        let interference =
+            Interference::between_meshes(&a, &b).expect("Meshes not empty");
+        let overlap = interference.overlap.expect("Cubes overlap");
+
+        assert_eq!(interference.distance, Scalar::ZERO);
+        assert_abs_diff_eq!(overlap.min.x, Scalar::from(1.5), epsilon = 1e-8);
+        assert_abs_diff_eq!(overlap.max.x, Scalar::from(2.), epsilon = 1e-8);
+    }
+
+    #[test]
+    fn contained() {
+        let a = Bvh::new(&Mesh::cube([0., 0., 0.], 3.));
+        let b = Bvh::new(&Mesh::cube([1., 1., 1.], 1.));
+
+        let interference =
+            Interference::between_meshes(&a, &b).expect("Meshes not empty");
+
+        assert_eq!(
+            interference.overlap,
+            Some(b.aabb().expect("Mesh not empty")),
+        );
+    }
+}
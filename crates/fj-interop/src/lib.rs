@@ -14,6 +14,8 @@
 
 #![warn(missing_docs)]
 
+pub mod bvh;
 pub mod debug;
+pub mod interference;
 pub mod mesh;
 pub mod processed_shape;
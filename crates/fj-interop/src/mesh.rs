@@ -1,6 +1,9 @@
 //! A triangle mesh
 
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
 
 use fj_math::Point;
 
@@ -11,6 +14,9 @@ pub struct Mesh<V> {
 
     indices_by_vertex: HashMap<V, Index>,
     triangles: Vec<Triangle>,
+
+    // Normalized copies of the triangles, to make `contains_triangle` cheap.
+    normalized_triangles: HashSet<fj_math::Triangle<3>>,
 }
 
 impl<V> Mesh<V>
@@ -43,15 +49,7 @@ where
         triangle: impl Into<fj_math::Triangle<3>>,
     ) -> bool {
         let triangle = triangle.into().normalize();
-
-        for t in &self.triangles {
-            let t = t.inner.normalize();
-            if triangle == t {
-                return true;
-            }
-        }
-
-        false
+        self.normalized_triangles.contains(&triangle)
     }
 
     /// Access the vertices of the mesh
@@ -87,6 +85,41 @@ impl Mesh<Point<3>> {
             inner: triangle,
             color,
         });
+        self.normalized_triangles.insert(triangle.normalize());
+    }
+
+    /// Construct an axis-aligned cube with outward-facing triangles
+    #[cfg(test)]
+    pub(crate) fn cube(origin: [f64; 3], size: f64) -> Self {
+        let mut mesh = Self::new();
+
+        let origin = Point::from(origin);
+        let [a, b, c, d, e, f, g, h] = [
+            [0., 0., 0.],
+            [1., 0., 0.],
+            [1., 1., 0.],
+            [0., 1., 0.],
+            [0., 0., 1.],
+            [1., 0., 1.],
+            [1., 1., 1.],
+            [0., 1., 1.],
+        ]
+        .map(|offset| origin + fj_math::Vector::from(offset) * size);
+
+        let quads = [
+            [a, d, c, b],
+            [e, f, g, h],
+            [a, b, f, e],
+            [b, c, g, f],
+            [c, d, h, g],
+            [d, a, e, h],
+        ];
+        for [a, b, c, d] in quads {
+            mesh.push_triangle([a, b, c], Color::default());
+            mesh.push_triangle([a, c, d], Color::default());
+        }
+
+        mesh
     }
 }
 
@@ -99,6 +132,7 @@ impl<V> Default for Mesh<V> {
             indices: Default::default(),
             indices_by_vertex: Default::default(),
             triangles: Default::default(),
+            normalized_triangles: Default::default(),
         }
     }
 }
@@ -2,7 +2,7 @@
 
 use fj_math::{Aabb, Point};
 
-use crate::{debug::DebugInfo, mesh::Mesh};
+use crate::{bvh::Bvh, debug::DebugInfo, mesh::Mesh};
 
 /// A processed shape
 pub struct ProcessedShape {
@@ -12,6 +12,9 @@ pub struct ProcessedShape {
     /// The triangle mesh that approximates the original shape
     pub mesh: Mesh<Point<3>>,
 
+    /// The bounding volume hierarchy over the triangles of `mesh`
+    pub bvh: Bvh,
+
     /// The debug info generated while processing the shape
     pub debug_info: DebugInfo,
 }
@@ -34,7 +34,8 @@ pub fn approx_curve(
 /// Approximate the circle
 ///
 /// `tolerance` specifies how much the approximation is allowed to deviate
-/// from the circle.
+/// from the circle. Any relative limit it defines is relative to the circle's
+/// radius.
 pub fn approx_circle(
     circle: &Circle<3>,
     tolerance: Tolerance,
@@ -58,10 +59,22 @@ pub fn approx_circle(
 }
 
 fn number_of_vertices_for_circle(tolerance: Tolerance, radius: Scalar) -> u64 {
-    let n = (Scalar::PI / (Scalar::ONE - (tolerance.inner() / radius)).acos())
+    // The radius is the size of the feature that is being approximated. Which
+    // means the allowed deviation can be relative to it.
+    let max_deviation = tolerance.for_feature_size(radius);
+
+    let n = (Scalar::PI / (Scalar::ONE - (max_deviation / radius)).acos())
         .ceil()
         .into_u64();
 
+    // Each segment of the regular polygon spans an angle of `2π / n`. The
+    // angle between the segment and the circle's tangent at either end of the
+    // segment is half of that.
+    let n = match tolerance.angular() {
+        Some(angular) => max(n, (Scalar::PI / angular).ceil().into_u64()),
+        None => n,
+    };
+
     max(n, 3)
 }
 
@@ -100,4 +113,52 @@ mod tests {
             radius - radius * (Scalar::PI / Scalar::from_u64(n)).cos()
         }
     }
+
+    #[test]
+    fn number_of_vertices_for_circle_with_limits() -> anyhow::Result<()> {
+        let tolerance = Tolerance::from_scalar(1.)?;
+
+        // With a relative limit, small circles are approximated with the same
+        // number of vertices as large ones.
+        let relative = tolerance.with_relative(0.01)?;
+        assert_eq!(
+            super::number_of_vertices_for_circle(relative, Scalar::from(0.1)),
+            super::number_of_vertices_for_circle(relative, Scalar::from(10.)),
+        );
+        assert_eq!(
+            super::number_of_vertices_for_circle(relative, Scalar::from(1000.)),
+            super::number_of_vertices_for_circle(
+                tolerance,
+                Scalar::from(1000.)
+            ),
+        );
+
+        // A 0.1 mm pin on a 1 m plate that is 10 mm thick. Deriving the
+        // absolute tolerance from the thickness of the plate results in a lot
+        // of vertices for a large circle. Deriving it from the size of the
+        // whole model, and relying on the relative limit for small features,
+        // doesn't.
+        let from_thickness =
+            Tolerance::from_scalar(0.01)?.with_relative(0.001)?;
+        let from_size = Tolerance::from_scalar(1.)?.with_relative(0.001)?;
+        let [pin, circle] = [0.05, 500.].map(Scalar::from);
+        assert_eq!(
+            super::number_of_vertices_for_circle(from_thickness, circle),
+            497,
+        );
+        assert_eq!(super::number_of_vertices_for_circle(from_size, circle), 71,);
+        assert_eq!(
+            super::number_of_vertices_for_circle(from_size, pin),
+            super::number_of_vertices_for_circle(from_thickness, pin),
+        );
+
+        // An angular limit of 10 degrees requires at least 18 vertices.
+        let angular = tolerance.with_angular(Scalar::PI / 18.)?;
+        assert_eq!(
+            super::number_of_vertices_for_circle(angular, Scalar::from(1.)),
+            18,
+        );
+
+        Ok(())
+    }
 }
@@ -8,6 +8,21 @@ use fj_math::Scalar;
 /// The `Tolerance` type enforces that the tolerance value is always larger than
 /// zero, which is an attribute that the approximation code relies on.
 ///
+/// # Adaptive approximation
+///
+/// A single absolute tolerance value doesn't serve models well, whose features
+/// differ in size by orders of magnitude. It either results in an enormous
+/// number of triangles for the large features, or in the small features being
+/// lost. To address this, a tolerance can define additional limits that are
+/// applied per feature (like a circular edge):
+///
+/// - A relative limit, which limits the deviation in relation to the size of
+///   the feature. See [`Tolerance::with_relative`]. With a relative limit, the
+///   absolute tolerance only serves as an upper bound, so it can be chosen
+///   based on the size of the whole model.
+/// - An angular limit, which limits the angle between the approximation and
+///   the actual shape. See [`Tolerance::with_angular`].
+///
 /// # Failing [`From`]/[`Into`] implementation
 ///
 /// The [`From`]/[`Into`] implementations of tolerance are fallible, which goes
@@ -18,7 +33,11 @@ use fj_math::Scalar;
 /// documentation doesn't provide any actual reasoning for this requirement, I'm
 /// feeling free to just ignore it.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
-pub struct Tolerance(Scalar);
+pub struct Tolerance {
+    absolute: Scalar,
+    relative: Option<Scalar>,
+    angular: Option<Scalar>,
+}
 
 impl Tolerance {
     /// Construct a `Tolerance` from a [`Scalar`]
@@ -27,18 +46,72 @@ impl Tolerance {
     pub fn from_scalar(
         scalar: impl Into<Scalar>,
     ) -> Result<Self, InvalidTolerance> {
-        let scalar = scalar.into();
+        let scalar = validate(scalar.into())?;
 
-        if scalar <= Scalar::ZERO {
-            return Err(InvalidTolerance(scalar));
-        }
+        Ok(Self {
+            absolute: scalar,
+            relative: None,
+            angular: None,
+        })
+    }
+
+    /// Limit the deviation in relation to the size of each feature
+    ///
+    /// For a feature of size `s` (like the radius of a circle), the allowed
+    /// deviation is then `relative * s`, capped by the absolute tolerance.
+    /// Large features can thus be approximated more coarsely than small ones.
+    ///
+    /// Returns an error, if the passed scalar is not larger than zero.
+    pub fn with_relative(
+        self,
+        relative: impl Into<Scalar>,
+    ) -> Result<Self, InvalidTolerance> {
+        Ok(Self {
+            relative: Some(validate(relative.into())?),
+            ..self
+        })
+    }
 
-        Ok(Self(scalar))
+    /// Limit the angle between the approximation and the actual shape
+    ///
+    /// The angle is given in radians. For a curve, it is the maximum angle
+    /// between the curve's tangent and an approximating line segment.
+    ///
+    /// Returns an error, if the passed scalar is not larger than zero.
+    pub fn with_angular(
+        self,
+        angular: impl Into<Scalar>,
+    ) -> Result<Self, InvalidTolerance> {
+        Ok(Self {
+            angular: Some(validate(angular.into())?),
+            ..self
+        })
     }
 
-    /// Return the [`Scalar`] that defines the tolerance
+    /// Return the [`Scalar`] that defines the absolute tolerance
     pub fn inner(&self) -> Scalar {
-        self.0
+        self.absolute
+    }
+
+    /// Return the relative tolerance, if one has been defined
+    pub fn relative(&self) -> Option<Scalar> {
+        self.relative
+    }
+
+    /// Return the angular tolerance, if one has been defined
+    pub fn angular(&self) -> Option<Scalar> {
+        self.angular
+    }
+
+    /// Return the maximum allowed deviation for a feature of the given size
+    ///
+    /// This is the absolute tolerance, unless a relative limit is defined. See
+    /// [`Tolerance::with_relative`].
+    pub fn for_feature_size(&self, size: Scalar) -> Scalar {
+        match self.relative {
+            Some(relative) => self.absolute.min(relative * size.abs()),
+            None => self.absolute,
+        }
     }
 }
 
@@ -52,7 +125,53 @@ where
     }
 }
 
+fn validate(scalar: Scalar) -> Result<Scalar, InvalidTolerance> {
+    if scalar <= Scalar::ZERO {
+        return Err(InvalidTolerance(scalar));
+    }
+
+    Ok(scalar)
+}
+
 /// Error converting scalar to tolerance
 #[derive(Debug, thiserror::Error)]
 #[error("Invalid tolerance ({0}); must be above zero")]
 pub struct InvalidTolerance(Scalar);
+
+#[cfg(test)]
+mod tests {
+    use fj_math::Scalar;
+
+    use super::Tolerance;
+
+    #[test]
+    fn for_feature_size() -> anyhow::Result<()> {
+        let tolerance = Tolerance::from_scalar(1.)?;
+        assert_eq!(
+            tolerance.for_feature_size(Scalar::from(0.5)),
+            Scalar::from(1.),
+        );
+
+        let tolerance = tolerance.with_relative(0.5)?;
+        assert_eq!(
+            tolerance.for_feature_size(Scalar::from(0.5)),
+            Scalar::from(0.25),
+        );
+        assert_eq!(
+            tolerance.for_feature_size(Scalar::from(4.)),
+            Scalar::from(1.),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_limits() -> anyhow::Result<()> {
+        let tolerance = Tolerance::from_scalar(1.)?;
+
+        assert!(tolerance.with_relative(0.).is_err());
+        assert!(tolerance.with_angular(-1.).is_err());
+
+        Ok(())
+    }
+}
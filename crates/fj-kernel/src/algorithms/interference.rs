@@ -0,0 +1,63 @@
+use fj_interop::{bvh::Bvh, debug::DebugInfo, interference::Interference};
+
+use crate::objects::Solid;
+
+use super::{triangulate, Tolerance};
+
+/// Check the interference between two solids
+///
+/// Determines whether the solids overlap and what their minimum distance is.
+/// The check is performed on triangulations of the solids, so the results are
+/// only as accurate as the provided `tolerance`.
+///
+/// Returns `None`, if either of the solids is empty.
+pub fn interference(
+    a: &Solid,
+    b: &Solid,
+    tolerance: Tolerance,
+) -> Option<Interference> {
+    let [a, b] = [a, b].map(|solid| {
+        let faces = solid.faces().cloned().collect();
+        let mesh = triangulate(faces, tolerance, &mut DebugInfo::new());
+        Bvh::new(&mesh)
+    });
+
+    Interference::between_meshes(&a, &b)
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_math::Scalar;
+
+    use crate::{
+        algorithms::{Tolerance, TransformObject},
+        objects::Solid,
+    };
+
+    #[test]
+    fn interference() -> anyhow::Result<()> {
+        let tolerance = Tolerance::from_scalar(Scalar::ONE)?;
+
+        let a = Solid::build().cube_from_edge_length(2.);
+        let b = Solid::build()
+            .cube_from_edge_length(2.)
+            .translate([5., 0., 0.]);
+        let c = Solid::build()
+            .cube_from_edge_length(2.)
+            .translate([1., 0., 0.]);
+
+        let disjoint = super::interference(&a, &b, tolerance)
+            .expect("Solids are not empty");
+        assert!(!disjoint.is_overlapping());
+        assert!(
+            (disjoint.distance - Scalar::from(3.)).abs() < Scalar::from(1e-8)
+        );
+
+        let overlapping = super::interference(&a, &c, tolerance)
+            .expect("Solids are not empty");
+        assert!(overlapping.is_overlapping());
+        assert_eq!(overlapping.distance, Scalar::ZERO);
+
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@
 //! on their respective purpose.
 
 mod approx;
+mod interference;
 mod reverse;
 mod sweep;
 mod transform;
@@ -13,8 +14,9 @@ pub mod intersection;
 
 pub use self::{
     approx::{CycleApprox, FaceApprox, InvalidTolerance, Tolerance},
+    interference::interference,
     reverse::reverse_face,
     sweep::sweep,
     transform::{transform_faces, TransformObject},
-    triangulate::triangulate,
+    triangulate::{triangulate, triangulate_face},
 };
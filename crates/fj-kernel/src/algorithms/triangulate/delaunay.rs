@@ -1,17 +1,102 @@
+use std::collections::{HashMap, VecDeque};
+
 use fj_math::{Point, Scalar, Triangle, Winding};
-use spade::HasPosition;
+use spade::{ConstrainedDelaunayTriangulation, HasPosition};
 
 use crate::local::Local;
 
-/// Create a Delaunay triangulation of all points
-pub fn triangulate(points: Vec<Local<Point<2>>>) -> Vec<[Local<Point<2>>; 3]> {
+/// Create a constrained Delaunay triangulation of a polygon
+///
+/// Expects the polygon as a list of cycles, each of them closed (meaning its
+/// last point is equal to its first). The edges of the cycles are inserted as
+/// constraints, which guarantees that they show up as edges in the
+/// triangulation.
+///
+/// Only returns the triangles within the polygon. Whether a triangle is inside
+/// is decided by the number of constraints that need to be crossed to reach it
+/// from outside the convex hull: Triangles that are reached by crossing an odd
+/// number of constraints are inside.
+pub fn triangulate(
+    cycles: impl IntoIterator<Item = Vec<Local<Point<2>>>>,
+) -> Vec<[Local<Point<2>>; 3]> {
     use spade::Triangulation as _;
 
-    let triangulation = spade::DelaunayTriangulation::<_>::bulk_load(points)
-        .expect("Inserted invalid values into triangulation");
+    let mut triangulation = ConstrainedDelaunayTriangulation::<_>::new();
+
+    for cycle in cycles {
+        let vertices = cycle
+            .into_iter()
+            .map(|point| {
+                triangulation
+                    .insert(point)
+                    .expect("Inserted invalid values into triangulation")
+            })
+            .collect::<Vec<_>>();
+
+        for edge in vertices.windows(2) {
+            let [a, b] = [edge[0], edge[1]];
+
+            // Consecutive points that are identical result in the same
+            // vertex, and there's no edge to constrain between those.
+            if a != b {
+                triangulation.add_constraint(a, b);
+            }
+        }
+    }
+
+    // Determine the number of constraints that need to be crossed to reach
+    // each face. Crossing a constraint has a cost of one, crossing any other
+    // edge a cost of zero, so this is a breadth-first search that puts
+    // zero-cost steps in front of the queue.
+    let mut depths = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    let enqueue = |queue: &mut VecDeque<_>, face, depth, edge| {
+        if triangulation.is_constraint_edge(edge) {
+            queue.push_back((face, depth + 1));
+        } else {
+            queue.push_front((face, depth));
+        }
+    };
+
+    for face in triangulation.inner_faces() {
+        for edge in face.adjacent_edges() {
+            if edge.rev().face().is_outer() {
+                enqueue(&mut queue, face.fix(), 0, edge.as_undirected().fix());
+            }
+        }
+    }
+
+    while let Some((face, depth)) = queue.pop_front() {
+        if depths.contains_key(&face) {
+            continue;
+        }
+        depths.insert(face, depth);
+
+        for edge in triangulation.face(face).adjacent_edges() {
+            if let Some(neighbor) = edge.rev().face().as_inner() {
+                if !depths.contains_key(&neighbor.fix()) {
+                    enqueue(
+                        &mut queue,
+                        neighbor.fix(),
+                        depth,
+                        edge.as_undirected().fix(),
+                    );
+                }
+            }
+        }
+    }
 
     let mut triangles = Vec::new();
     for triangle in triangulation.inner_faces() {
+        let is_inside = depths
+            .get(&triangle.fix())
+            .map(|depth| depth % 2 == 1)
+            .unwrap_or(false);
+        if !is_inside {
+            continue;
+        }
+
         let [v0, v1, v2] = triangle.vertices().map(|vertex| *vertex.data());
         let orientation = Triangle::<2>::from_points([
             *v0.local_form(),
@@ -2,8 +2,11 @@ mod delaunay;
 mod polygon;
 mod ray;
 
-use fj_interop::{debug::DebugInfo, mesh::Mesh};
-use fj_math::Point;
+use fj_interop::{
+    debug::DebugInfo,
+    mesh::{Color, Mesh},
+};
+use fj_math::{Point, Triangle};
 
 use crate::objects::Face;
 
@@ -12,52 +15,101 @@ use self::polygon::Polygon;
 use super::{FaceApprox, Tolerance};
 
 /// Triangulate a shape
+///
+/// If the `parallel` feature is enabled, the faces are triangulated in
+/// parallel. The resulting mesh is the same either way, with the triangles of
+/// each face in the order of the faces.
 pub fn triangulate(
     faces: Vec<Face>,
     tolerance: Tolerance,
     debug_info: &mut DebugInfo,
 ) -> Mesh<Point<3>> {
+    let process = |face: &Face| {
+        let mut face_debug_info = debug_info.branch();
+        let triangles = triangulate_face(face, tolerance, &mut face_debug_info);
+        (triangles, face_debug_info)
+    };
+
+    #[cfg(feature = "parallel")]
+    let results: Vec<_> = {
+        use rayon::prelude::*;
+        faces.par_iter().map(process).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<_> = faces.iter().map(process).collect();
+
     let mut mesh = Mesh::new();
 
-    for face in faces {
-        if let Some(triangles) = face.triangles() {
-            for &(triangle, color) in triangles {
-                mesh.push_triangle(triangle, color);
-            }
-            continue;
+    for (triangles, face_debug_info) in results {
+        for (triangle, color) in triangles {
+            mesh.push_triangle(triangle, color);
         }
+        debug_info.append(face_debug_info);
+    }
+
+    mesh
+}
 
-        let surface = face.surface();
-        let approx = FaceApprox::new(&face, tolerance);
+/// Triangulate a single face
+///
+/// Returns the triangles of the face, each with the color of the face.
+pub fn triangulate_face(
+    face: &Face,
+    tolerance: Tolerance,
+    debug_info: &mut DebugInfo,
+) -> Vec<(Triangle<3>, Color)> {
+    if let Some(triangles) = face.triangles() {
+        return triangles.clone();
+    }
 
-        let points: Vec<_> = approx.points.into_iter().collect();
+    let surface = face.surface();
+    let approx = FaceApprox::new(face, tolerance);
+
+    let exterior = approx.exterior.points;
+    let mut interiors = approx
+        .interiors
+        .into_iter()
+        .map(|interior| interior.points)
+        .collect::<Vec<_>>();
+
+    // The interiors come out of a `HashSet`. Sort them, to make sure the
+    // triangulation doesn't depend on their order.
+    interiors.sort();
+
+    let triangles = delaunay::triangulate(
+        Some(exterior.clone())
+            .into_iter()
+            .chain(interiors.iter().cloned()),
+    );
+
+    // The constrained triangulation only returns triangles within the
+    // face, so checking the triangles against the face is not required.
+    // It is still useful as a diagnostic though, as it provides
+    // information that can be visualized.
+    if debug_info.record_triangle_edge_checks {
         let face_as_polygon = Polygon::new(*surface)
             .with_exterior(
-                approx
-                    .exterior
-                    .points
-                    .into_iter()
-                    .map(|point| *point.local_form()),
+                exterior.into_iter().map(|point| *point.local_form()),
             )
-            .with_interiors(approx.interiors.into_iter().map(|interior| {
-                interior.points.into_iter().map(|point| *point.local_form())
+            .with_interiors(interiors.into_iter().map(|interior| {
+                interior.into_iter().map(|point| *point.local_form())
             }));
 
-        let mut triangles = delaunay::triangulate(points);
-        triangles.retain(|triangle| {
+        for triangle in &triangles {
             face_as_polygon.contains_triangle(
                 triangle.map(|point| *point.local_form()),
                 debug_info,
-            )
-        });
-
-        for triangle in triangles {
-            let points = triangle.map(|point| *point.global_form());
-            mesh.push_triangle(points, face.color());
+            );
         }
     }
 
-    mesh
+    triangles
+        .into_iter()
+        .map(|triangle| {
+            let points = triangle.map(|point| *point.global_form());
+            (Triangle::from(points), face.color())
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -135,7 +187,6 @@ mod tests {
         Ok(())
     }
 
-    #[ignore]
     #[test]
     fn sharp_concave_shape() -> anyhow::Result<()> {
         //
@@ -152,7 +203,6 @@ mod tests {
 
         let a = Point::from([0., 0.]);
         let b = Point::from([0.4, 0.]);
-        //let b = Point::from([0.5, 0.]); // test passes with this change
         let c = Point::from([0.4, 1.0]);
         let d = Point::from([0.1, 0.1]);
         let e = Point::from([0., 0.8]);
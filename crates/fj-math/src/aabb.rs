@@ -1,6 +1,9 @@
-use parry3d_f64::bounding_volume::BoundingVolume as _;
+use parry3d_f64::{
+    bounding_volume::BoundingVolume as _,
+    query::{PointQuery as _, Ray, RayCast as _},
+};
 
-use super::{Point, Vector};
+use super::{Point, Scalar, Vector};
 
 /// An axis-aligned bounding box (AABB)
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
@@ -126,6 +129,67 @@ impl Aabb<3> {
     pub fn merged(&self, other: &Self) -> Self {
         self.to_parry().merged(&other.to_parry()).into()
     }
+
+    /// Determine whether this AABB overlaps with another
+    ///
+    /// AABBs that merely touch are considered to overlap.
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.to_parry().intersects(&other.to_parry())
+    }
+
+    /// Compute the distance between the AABB and a point
+    ///
+    /// Returns zero, if the point is within the AABB.
+    pub fn distance_to_point(&self, point: &Point<3>) -> Scalar {
+        self.to_parry()
+            .distance_to_local_point(&point.to_na(), true)
+            .into()
+    }
+
+    /// Compute the distance between this AABB and another
+    ///
+    /// Returns zero, if the AABBs overlap.
+    pub fn distance_to_aabb(&self, other: &Self) -> Scalar {
+        let mut gap = Vector::from([0., 0., 0.]);
+
+        for i in 0..3 {
+            let below =
+                other.min.coords.components[i] - self.max.coords.components[i];
+            let above =
+                self.min.coords.components[i] - other.max.coords.components[i];
+
+            gap.components[i] = below.max(above).max(Scalar::ZERO);
+        }
+
+        gap.magnitude()
+    }
+
+    /// Compute the intersection of this AABB and another
+    ///
+    /// Returns `None`, if the AABBs don't overlap.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        self.to_parry()
+            .intersection(&other.to_parry())
+            .map(Into::into)
+    }
+
+    /// Cast a ray against the AABB
+    pub fn cast_local_ray(
+        &self,
+        origin: Point<3>,
+        dir: Vector<3>,
+        max_toi: f64,
+        solid: bool,
+    ) -> Option<Scalar> {
+        let ray = Ray {
+            origin: origin.to_na(),
+            dir: dir.to_na(),
+        };
+
+        self.to_parry()
+            .cast_local_ray(&ray, max_toi, solid)
+            .map(|f| f.into())
+    }
 }
 
 impl From<parry2d_f64::bounding_volume::AABB> for Aabb<2> {
@@ -142,6 +206,8 @@ impl From<parry3d_f64::bounding_volume::AABB> for Aabb<3> {
 
 #[cfg(test)]
 mod tests {
+    use crate::Scalar;
+
     use super::Aabb;
 
     #[test]
@@ -160,4 +226,28 @@ mod tests {
         assert!(!aabb.contains([0., 2.]));
         assert!(!aabb.contains([4., 2.]));
     }
+
+    #[test]
+    fn intersects() {
+        let aabb = Aabb::<3>::from_points([[0., 0., 0.], [2., 2., 2.]]);
+
+        let overlapping = Aabb::<3>::from_points([[1., 1., 1.], [3., 3., 3.]]);
+        let touching = Aabb::<3>::from_points([[2., 0., 0.], [3., 2., 2.]]);
+        let disjoint = Aabb::<3>::from_points([[3., 3., 3.], [4., 4., 4.]]);
+
+        assert!(aabb.intersects(&overlapping));
+        assert!(aabb.intersects(&touching));
+        assert!(!aabb.intersects(&disjoint));
+    }
+
+    #[test]
+    fn distance_to_aabb() {
+        let aabb = Aabb::<3>::from_points([[0., 0., 0.], [1., 1., 1.]]);
+
+        let overlapping = Aabb::<3>::from_points([[0., 0., 0.], [2., 2., 2.]]);
+        let apart = Aabb::<3>::from_points([[4., 5., 0.], [5., 6., 1.]]);
+
+        assert_eq!(aabb.distance_to_aabb(&overlapping), Scalar::ZERO);
+        assert_eq!(aabb.distance_to_aabb(&apart), Scalar::from(5.));
+    }
 }
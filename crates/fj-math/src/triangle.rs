@@ -1,7 +1,10 @@
 use parry2d_f64::utils::point_in_triangle::{corner_direction, Orientation};
-use parry3d_f64::query::{Ray, RayCast as _};
+use parry3d_f64::{
+    math::Isometry,
+    query::{ClosestPoints, PointQuery as _, Ray, RayCast as _},
+};
 
-use crate::Vector;
+use crate::{Aabb, Vector};
 
 use super::{Point, Scalar};
 
@@ -88,6 +91,59 @@ impl Triangle<3> {
             .map(|f| f.into())
     }
 
+    /// Compute the point on the triangle that is closest to the given point
+    pub fn closest_point(&self, point: &Point<3>) -> Point<3> {
+        self.to_parry()
+            .project_local_point(&point.to_na(), true)
+            .point
+            .into()
+    }
+
+    /// Determine whether this triangle intersects another
+    ///
+    /// Triangles that merely touch are considered to intersect.
+    pub fn intersects(&self, other: &Self) -> bool {
+        let identity = Isometry::identity();
+
+        parry3d_f64::query::intersection_test(
+            &identity,
+            &self.to_parry(),
+            &identity,
+            &other.to_parry(),
+        )
+        .expect("Intersection test between triangles is supported")
+    }
+
+    /// Compute the closest points between this triangle and another
+    ///
+    /// Returns the point on this triangle first, the point on `other` second.
+    /// Returns `None`, if the triangles intersect.
+    pub fn closest_points(&self, other: &Self) -> Option<[Point<3>; 2]> {
+        let identity = Isometry::identity();
+
+        let closest_points = parry3d_f64::query::closest_points(
+            &identity,
+            &self.to_parry(),
+            &identity,
+            &other.to_parry(),
+            f64::MAX,
+        )
+        .expect("Closest points between triangles are supported");
+
+        match closest_points {
+            ClosestPoints::Intersecting => None,
+            ClosestPoints::WithinMargin(a, b) => Some([a.into(), b.into()]),
+            ClosestPoints::Disjoint => {
+                unreachable!("Triangles are always within infinite margin")
+            }
+        }
+    }
+
+    /// Compute the axis-aligned bounding box of the triangle
+    pub fn aabb(&self) -> Aabb<3> {
+        Aabb::<3>::from_points(self.points)
+    }
+
     /// Compute the triangle's normal
     pub fn normal(&self) -> Vector<3> {
         self.to_parry()
@@ -127,7 +183,9 @@ impl From<Orientation> for Winding {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Point, Vector};
+    use approx::assert_abs_diff_eq;
+
+    use crate::{Point, Scalar, Vector};
 
     use super::Triangle;
 
@@ -171,4 +229,58 @@ mod tests {
             Triangle::from([[0.0, 0.0, 0.0], [2.0, 1.0, 0.0], [2.0, 0.0, 0.0]]);
         assert_eq!(triangle.normal(), Vector::from([0.0, 0.0, -1.0]));
     }
+
+    #[test]
+    fn intersects() {
+        let triangle =
+            Triangle::from([[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, 0.0]]);
+
+        let crossing = Triangle::from([
+            [0.5, 0.5, -1.0],
+            [0.5, 0.5, 1.0],
+            [1.0, 0.0, 1.0],
+        ]);
+        let above =
+            Triangle::from([[0.0, 0.0, 1.0], [2.0, 0.0, 1.0], [0.0, 2.0, 1.0]]);
+
+        assert!(triangle.intersects(&crossing));
+        assert!(!triangle.intersects(&above));
+    }
+
+    #[test]
+    fn closest_points() {
+        let triangle =
+            Triangle::from([[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, 0.0]]);
+        let above =
+            Triangle::from([[0.5, 0.5, 1.0], [3.0, 0.5, 1.0], [0.5, 3.0, 1.0]]);
+
+        let [a, b] = triangle
+            .closest_points(&above)
+            .expect("Triangles don't intersect");
+        assert_abs_diff_eq!(
+            Point::distance(&a, &b),
+            Scalar::ONE,
+            epsilon = 1e-8,
+        );
+    }
+
+    #[test]
+    fn closest_point() {
+        let triangle =
+            Triangle::from([[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, 0.0]]);
+
+        let above = Point::from([0.5, 0.5, 1.0]);
+        let outside = Point::from([-1.0, 1.0, 0.0]);
+
+        assert_abs_diff_eq!(
+            triangle.closest_point(&above),
+            Point::from([0.5, 0.5, 0.0]),
+            epsilon = 1e-8,
+        );
+        assert_abs_diff_eq!(
+            triangle.closest_point(&outside),
+            Point::from([0.0, 1.0, 0.0]),
+            epsilon = 1e-8,
+        );
+    }
 }
@@ -0,0 +1,505 @@
+//! Caching of shape processing results
+
+use std::{
+    collections::HashMap,
+    fs,
+    hash::{Hash, Hasher},
+    mem,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::SystemTime,
+};
+
+use fj_interop::{
+    debug::DebugInfo,
+    mesh::{Color, Mesh},
+};
+use fj_kernel::{
+    algorithms::{triangulate, triangulate_face, Tolerance},
+    objects::Face,
+    validation::Validated,
+};
+use fj_math::{Point, Triangle};
+
+use crate::{BrepError, ImportError};
+
+/// Cache for the results of shape processing
+///
+/// Caches the boundary representation of each [`fj::Shape`] subtree, the
+/// triangles of each face, and the triangles loaded from each imported file.
+/// Those results are reused, as long as the subtree, face, or file, and the
+/// tolerance, are unchanged.
+///
+/// Each call to [`Cache::next_generation`] evicts the entries that haven't
+/// been used since the previous call. As long as it is called once per
+/// processed shape, the cache only holds results that are relevant to the
+/// latest version of a model.
+#[derive(Default)]
+pub struct Cache {
+    breps: Mutex<Generations<ShapeKey, Validated<Vec<Face>>>>,
+    meshes: Mutex<Generations<FaceKey, Vec<(Triangle<3>, Color)>>>,
+    imports: Mutex<Generations<ImportKey, Arc<Vec<(Triangle<3>, Color)>>>>,
+}
+
+impl Cache {
+    /// Construct an empty instance of `Cache`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new generation of cache entries
+    ///
+    /// Evicts all entries that haven't been used since the last call.
+    pub fn next_generation(&self) {
+        lock(&self.breps).next_generation();
+        lock(&self.meshes).next_generation();
+        lock(&self.imports).next_generation();
+    }
+
+    /// Remove all entries from the cache
+    pub fn clear(&self) {
+        *lock(&self.breps) = Generations::default();
+        *lock(&self.meshes) = Generations::default();
+        *lock(&self.imports) = Generations::default();
+    }
+
+    /// Return the cached B-rep of a shape, or compute and cache it
+    ///
+    /// The cache isn't locked while `compute` runs, so `compute` is free to
+    /// use the cache for the shape's children.
+    pub(crate) fn brep(
+        &self,
+        shape: &fj::Shape,
+        tolerance: Tolerance,
+        compute: impl FnOnce() -> Result<Validated<Vec<Face>>, BrepError>,
+    ) -> Result<Validated<Vec<Face>>, BrepError> {
+        let mut imports = Vec::new();
+        modification_times(shape, &mut imports);
+
+        let key = ShapeKey {
+            shape: shape.clone(),
+            tolerance,
+            imports,
+        };
+
+        if let Some(brep) = lock(&self.breps).get(&key) {
+            return Ok(brep);
+        }
+
+        let brep = compute()?;
+        lock(&self.breps).insert(key, brep.clone());
+
+        Ok(brep)
+    }
+
+    /// Return the cached triangles of an imported file, or load and cache them
+    ///
+    /// The triangles are loaded again, if the file has been modified since.
+    /// Errors are not cached.
+    pub(crate) fn import(
+        &self,
+        import: &fj::Import,
+        load: impl FnOnce() -> Result<Vec<(Triangle<3>, Color)>, ImportError>,
+    ) -> Result<Arc<Vec<(Triangle<3>, Color)>>, ImportError> {
+        let key = ImportKey {
+            path: import.path().to_owned(),
+            color: import.color(),
+            modified: modification_time(import.path()),
+        };
+
+        if let Some(triangles) = lock(&self.imports).get(&key) {
+            return Ok(triangles);
+        }
+
+        let triangles = Arc::new(load()?);
+        lock(&self.imports).insert(key, triangles.clone());
+
+        Ok(triangles)
+    }
+
+    /// Triangulate faces, reusing the cached triangles of unchanged faces
+    ///
+    /// Produces the same mesh as [`triangulate`].
+    pub(crate) fn triangulate(
+        &self,
+        faces: Vec<Face>,
+        tolerance: Tolerance,
+        debug_info: &mut DebugInfo,
+    ) -> Mesh<Point<3>> {
+        // Triangle edge checks are recorded during triangulation. If we want
+        // them for all faces, we can't skip triangulating any of them.
+        if debug_info.record_triangle_edge_checks {
+            return triangulate(faces, tolerance, debug_info);
+        }
+
+        let keys = faces
+            .into_iter()
+            .map(|face| FaceKey { face, tolerance })
+            .collect::<Vec<_>>();
+
+        let cached = {
+            let mut meshes = lock(&self.meshes);
+            keys.iter().map(|key| meshes.get(key)).collect::<Vec<_>>()
+        };
+
+        let process = |(key, cached): (&FaceKey, Option<_>)| {
+            cached.unwrap_or_else(|| {
+                triangulate_face(&key.face, tolerance, &mut DebugInfo::new())
+            })
+        };
+
+        #[cfg(feature = "parallel")]
+        let triangles: Vec<_> = {
+            use rayon::prelude::*;
+            keys.par_iter().zip(cached).map(process).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let triangles: Vec<_> = keys.iter().zip(cached).map(process).collect();
+
+        let mut mesh = Mesh::new();
+        let mut meshes = lock(&self.meshes);
+
+        for (key, triangles) in keys.into_iter().zip(triangles) {
+            for &(triangle, color) in &triangles {
+                mesh.push_triangle(triangle, color);
+            }
+            meshes.insert(key, triangles);
+        }
+
+        mesh
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
+    // A panic while the lock is held can't leave the cache in an inconsistent
+    // state, so we can safely ignore the poisoning.
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Cache entries, grouped by whether they have been used recently
+struct Generations<K, V> {
+    current: HashMap<K, V>,
+    previous: HashMap<K, V>,
+}
+
+impl<K, V> Generations<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    fn get(&mut self, key: &K) -> Option<V> {
+        if let Some(value) = self.current.get(key) {
+            return Some(value.clone());
+        }
+
+        let (key, value) = self.previous.remove_entry(key)?;
+        self.current.insert(key, value.clone());
+
+        Some(value)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.current.insert(key, value);
+    }
+
+    fn next_generation(&mut self) {
+        self.previous = mem::take(&mut self.current);
+    }
+}
+
+impl<K, V> Default for Generations<K, V> {
+    fn default() -> Self {
+        Self {
+            current: HashMap::new(),
+            previous: HashMap::new(),
+        }
+    }
+}
+
+struct ShapeKey {
+    shape: fj::Shape,
+    tolerance: Tolerance,
+
+    /// The modification times of the files imported by the shape
+    ///
+    /// Imported files can change without the shape changing. Including their
+    /// modification times in the key makes sure those changes are picked up.
+    imports: Vec<Option<SystemTime>>,
+}
+
+impl PartialEq for ShapeKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape == other.shape
+            && self.tolerance == other.tolerance
+            && self.imports == other.imports
+    }
+}
+
+// Not strictly true, as a shape containing NaN is not equal to itself. Such a
+// shape can never be found in the cache, which is fine.
+impl Eq for ShapeKey {}
+
+impl Hash for ShapeKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.shape.hash_shape(state);
+        self.tolerance.hash(state);
+        self.imports.hash(state);
+    }
+}
+
+fn modification_times(shape: &fj::Shape, times: &mut Vec<Option<SystemTime>>) {
+    match shape {
+        fj::Shape::Group(group) => {
+            modification_times(&group.a, times);
+            modification_times(&group.b, times);
+        }
+        fj::Shape::Import(import) => {
+            times.push(modification_time(import.path()));
+        }
+        fj::Shape::Shape2d(_) | fj::Shape::Sweep(_) => {}
+        fj::Shape::Transform(transform) => {
+            modification_times(&transform.shape, times);
+        }
+    }
+}
+
+fn modification_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[derive(Eq, PartialEq, Hash)]
+struct ImportKey {
+    path: String,
+    color: [u8; 4],
+
+    /// The modification time of the file, if available
+    modified: Option<SystemTime>,
+}
+
+#[derive(Eq, PartialEq, Hash)]
+struct FaceKey {
+    face: Face,
+    tolerance: Tolerance,
+}
+
+/// Hashing for the types from the [`fj`] crate
+///
+/// Those types contain floating-point numbers, so they can't implement
+/// [`Hash`]. Hashes computed with this trait are consistent with the
+/// [`PartialEq`] implementations of those types.
+trait HashShape {
+    fn hash_shape<H: Hasher>(&self, state: &mut H);
+}
+
+impl HashShape for fj::Shape {
+    fn hash_shape<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            Self::Group(group) => {
+                group.a.hash_shape(state);
+                group.b.hash_shape(state);
+            }
+            Self::Import(import) => {
+                import.path().hash(state);
+                import.color().hash(state);
+            }
+            Self::Shape2d(shape) => shape.hash_shape(state),
+            Self::Sweep(sweep) => {
+                sweep.shape().hash_shape(state);
+                hash_f64s(&sweep.path(), state);
+            }
+            Self::Transform(transform) => {
+                transform.shape.hash_shape(state);
+                hash_f64s(&transform.axis, state);
+                hash_f64s(&[transform.angle.rad()], state);
+                hash_f64s(&transform.offset, state);
+            }
+        }
+    }
+}
+
+impl HashShape for fj::Shape2d {
+    fn hash_shape<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            Self::Difference(difference) => {
+                for shape in difference.shapes() {
+                    shape.hash_shape(state);
+                }
+            }
+            Self::Sketch(sketch) => {
+                mem::discriminant(sketch.chain()).hash(state);
+
+                match sketch.chain() {
+                    fj::Chain::Circle(circle) => {
+                        hash_f64s(&[circle.radius()], state);
+                    }
+                    fj::Chain::PolyChain(poly_chain) => {
+                        for point in poly_chain.to_points() {
+                            hash_f64s(&point, state);
+                        }
+                    }
+                }
+
+                sketch.color().hash(state);
+            }
+        }
+    }
+}
+
+fn hash_f64s<H: Hasher>(values: &[f64], state: &mut H) {
+    for &value in values {
+        // `0.0` and `-0.0` are equal, so they need to result in the same hash.
+        let value = if value == 0. { 0. } else { value };
+        value.to_bits().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    };
+
+    use fj_kernel::{
+        algorithms::Tolerance,
+        validation::{validate, ValidationConfig},
+    };
+
+    use crate::shape_processor::ShapeProcessor;
+
+    use super::{lock, Cache, ShapeKey};
+
+    #[test]
+    fn equal_shapes_have_equal_hashes() {
+        let a =
+            key(fj::Sketch::from_points(vec![[0., 0.], [1., 0.], [0., 1.]]));
+        let b =
+            key(fj::Sketch::from_points(vec![[-0., 0.], [1., 0.], [0., 1.]]));
+
+        assert!(a == b);
+        assert_eq!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    fn different_shapes_have_different_hashes() {
+        let a =
+            key(fj::Sketch::from_points(vec![[0., 0.], [1., 0.], [0., 1.]]));
+        let b =
+            key(fj::Sketch::from_points(vec![[0., 0.], [2., 0.], [0., 1.]]));
+
+        assert!(a != b);
+        assert_ne!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    fn second_process_reuses_cached_results() {
+        let processor = processor();
+        let shape = fj::Shape::from(fj::Group {
+            a: square(0., 1.).into(),
+            b: square(2., 1.).into(),
+        });
+
+        let first = processor.process(&shape).unwrap();
+        let second = processor.process(&shape).unwrap();
+
+        // Entries that are used again are moved to the current generation.
+        // Anything left in the previous one has been computed again.
+        let breps = lock(&processor.cache.breps);
+        let meshes = lock(&processor.cache.meshes);
+        assert_eq!(breps.current.len(), 3);
+        assert!(breps.previous.is_empty());
+        assert!(!meshes.current.is_empty());
+        assert!(meshes.previous.is_empty());
+
+        assert_eq!(
+            first.mesh.triangles().collect::<Vec<_>>(),
+            second.mesh.triangles().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn changing_a_subtree_only_invalidates_that_subtree() {
+        let processor = processor();
+        let tolerance = processor.tolerance.unwrap();
+
+        let a = fj::Shape::from(square(0., 1.));
+        let b = fj::Shape::from(square(2., 1.));
+        let c = fj::Shape::from(square(2., 2.));
+
+        processor
+            .process(&fj::Group { a: a.clone(), b }.into())
+            .unwrap();
+        processor
+            .process(
+                &fj::Group {
+                    a: a.clone(),
+                    b: c.clone(),
+                }
+                .into(),
+            )
+            .unwrap();
+
+        // Only the group and the changed shape weren't used again.
+        assert_eq!(lock(&processor.cache.breps).previous.len(), 2);
+
+        let computed = Cell::new(0);
+        let brep = |shape: &fj::Shape| {
+            processor
+                .cache
+                .brep(shape, tolerance, || {
+                    computed.set(computed.get() + 1);
+                    Ok(validate(Vec::new(), &ValidationConfig::default())?)
+                })
+                .unwrap();
+        };
+
+        brep(&a);
+        brep(&c);
+        assert_eq!(computed.get(), 0);
+
+        // The old version of the changed shape is evicted with the next
+        // generation.
+        processor.cache.next_generation();
+        brep(&fj::Shape::from(square(2., 1.)));
+        assert_eq!(computed.get(), 1);
+    }
+
+    fn processor() -> ShapeProcessor {
+        ShapeProcessor {
+            tolerance: Some(Tolerance::from_scalar(0.01).unwrap()),
+            relative_tolerance: None,
+            angular_tolerance: None,
+            record_triangle_edge_checks: false,
+            cache: Cache::new(),
+        }
+    }
+
+    fn square(x: f64, size: f64) -> fj::Sketch {
+        fj::Sketch::from_points(vec![
+            [x, 0.],
+            [x + size, 0.],
+            [x + size, size],
+            [x, size],
+        ])
+    }
+
+    fn key(sketch: fj::Sketch) -> ShapeKey {
+        ShapeKey {
+            shape: sketch.into(),
+            tolerance: Tolerance::from_scalar(1.).unwrap(),
+            imports: Vec::new(),
+        }
+    }
+
+    fn hash(key: &ShapeKey) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+}
@@ -4,11 +4,11 @@ use fj_kernel::{
     iter::ObjectIters,
     local::Local,
     objects::{Cycle, Edge, Face, Sketch},
-    validation::{validate, Validated, ValidationConfig, ValidationError},
+    validation::{validate, Validated, ValidationConfig},
 };
 use fj_math::Aabb;
 
-use super::Shape;
+use super::{compute_brep_pair, BrepError, Cache, Shape};
 
 impl Shape for fj::Difference2d {
     type Brep = Sketch;
@@ -17,8 +17,9 @@ impl Shape for fj::Difference2d {
         &self,
         config: &ValidationConfig,
         tolerance: Tolerance,
+        cache: &Cache,
         debug_info: &mut DebugInfo,
-    ) -> Result<Validated<Self::Brep>, ValidationError> {
+    ) -> Result<Validated<Self::Brep>, BrepError> {
         // This method assumes that `b` is fully contained within `a`:
         // https://github.com/hannobraun/Fornjot/issues/92
 
@@ -27,13 +28,9 @@ impl Shape for fj::Difference2d {
         let mut exteriors = Vec::new();
         let mut interiors = Vec::new();
 
-        // Can be cleaned up, once `each_ref` and `try_map` are stable:
-        // - https://doc.rust-lang.org/std/primitive.array.html#method.each_ref
-        // - https://doc.rust-lang.org/std/primitive.array.html#method.try_map
         let [a, b] = self.shapes();
-        let [a, b] = [a, b]
-            .map(|shape| shape.compute_brep(config, tolerance, debug_info));
-        let [a, b] = [a?, b?];
+        let (a, b) =
+            compute_brep_pair(a, b, config, tolerance, cache, debug_info)?;
 
         if let Some(face) = a.face_iter().next() {
             // If there's at least one face to subtract from, we can proceed.
@@ -79,14 +76,14 @@ impl Shape for fj::Difference2d {
         }
 
         let difference = Sketch::new().with_faces(faces);
-        validate(difference, config)
+        Ok(validate(difference, config)?)
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
+    fn bounding_volume(&self, cache: &Cache) -> Result<Aabb<3>, BrepError> {
         // This is a conservative estimate of the bounding box: It's never going
         // to be bigger than the bounding box of the original shape that another
         // is being subtracted from.
-        self.shapes()[0].bounding_volume()
+        self.shapes()[0].bounding_volume(cache)
     }
 }
 
@@ -2,11 +2,11 @@ use fj_interop::debug::DebugInfo;
 use fj_kernel::{
     algorithms::Tolerance,
     objects::Face,
-    validation::{validate, Validated, ValidationConfig, ValidationError},
+    validation::{validate, Validated, ValidationConfig},
 };
 use fj_math::Aabb;
 
-use super::Shape;
+use super::{compute_brep_pair, BrepError, Cache, Shape};
 
 impl Shape for fj::Group {
     type Brep = Vec<Face>;
@@ -15,23 +15,25 @@ impl Shape for fj::Group {
         &self,
         config: &ValidationConfig,
         tolerance: Tolerance,
+        cache: &Cache,
         debug_info: &mut DebugInfo,
-    ) -> Result<Validated<Self::Brep>, ValidationError> {
+    ) -> Result<Validated<Self::Brep>, BrepError> {
         let mut faces = Vec::new();
 
-        let a = self.a.compute_brep(config, tolerance, debug_info)?;
-        let b = self.b.compute_brep(config, tolerance, debug_info)?;
+        let (a, b) = compute_brep_pair(
+            &self.a, &self.b, config, tolerance, cache, debug_info,
+        )?;
 
         faces.extend(a.into_inner());
         faces.extend(b.into_inner());
 
-        validate(faces, config)
+        Ok(validate(faces, config)?)
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
-        let a = self.a.bounding_volume();
-        let b = self.b.bounding_volume();
+    fn bounding_volume(&self, cache: &Cache) -> Result<Aabb<3>, BrepError> {
+        let a = self.a.bounding_volume(cache)?;
+        let b = self.b.bounding_volume(cache)?;
 
-        a.merged(&b)
+        Ok(a.merged(&b))
     }
 }
@@ -0,0 +1,152 @@
+//! Import of triangle meshes from files
+
+mod stl;
+mod three_mf;
+
+use std::{fs, io, path::Path};
+
+use fj_interop::{debug::DebugInfo, mesh::Color};
+use fj_kernel::{
+    algorithms::Tolerance,
+    objects::Face,
+    validation::{validate, Validated, ValidationConfig},
+};
+use fj_math::{Aabb, Point, Triangle};
+
+use crate::{BrepError, Cache, Shape};
+
+impl Shape for fj::Import {
+    type Brep = Vec<Face>;
+
+    fn compute_brep(
+        &self,
+        config: &ValidationConfig,
+        _: Tolerance,
+        cache: &Cache,
+        _: &mut DebugInfo,
+    ) -> Result<Validated<Self::Brep>, BrepError> {
+        let triangles = cache.import(self, || load(self))?;
+
+        let faces = if triangles.is_empty() {
+            Vec::new()
+        } else {
+            vec![Face::from_triangles(triangles.to_vec())]
+        };
+
+        Ok(validate(faces, config)?)
+    }
+
+    fn bounding_volume(&self, cache: &Cache) -> Result<Aabb<3>, BrepError> {
+        let triangles = cache.import(self, || load(self))?;
+
+        if triangles.is_empty() {
+            return Ok(Aabb {
+                min: Point::origin(),
+                max: Point::origin(),
+            });
+        }
+
+        Ok(Aabb::<3>::from_points(
+            triangles.iter().flat_map(|(triangle, _)| triangle.points()),
+        ))
+    }
+}
+
+/// Load the triangles of an imported file
+///
+/// Degenerate triangles, as they commonly occur in scanned meshes, are
+/// skipped.
+fn load(import: &fj::Import) -> Result<Vec<(Triangle<3>, Color)>, ImportError> {
+    let path = Path::new(import.path());
+
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_uppercase());
+    let read = match extension.as_deref() {
+        Some("STL") => stl::read,
+        Some("3MF") => three_mf::read,
+        _ => return Err(ImportError::InvalidExtension(import.path().into())),
+    };
+
+    let data = fs::read(path).map_err(|source| ImportError::Io {
+        path: import.path().into(),
+        source,
+    })?;
+    let triangles = read(&data).map_err(|reason| ImportError::Invalid {
+        path: import.path().into(),
+        reason,
+    })?;
+
+    let default_color = Color(import.color());
+    let triangles = triangles
+        .into_iter()
+        .filter_map(|(points, color)| {
+            let triangle = Triangle::from_points(points)?;
+            Some((triangle, color.unwrap_or(default_color)))
+        })
+        .collect();
+
+    Ok(triangles)
+}
+
+/// The triangles read from a file
+///
+/// Triangles that don't define their own color get the color of the import.
+type Triangles = Vec<([Point<3>; 3], Option<Color>)>;
+
+/// An error that can occur while importing a file
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    /// Unrecognized file extension
+    #[error("Unrecognized extension of imported file `{0}`")]
+    InvalidExtension(String),
+
+    /// I/O error while reading the file
+    #[error("I/O error while importing `{path}`")]
+    Io {
+        /// The path of the imported file
+        path: String,
+
+        /// The I/O error
+        #[source]
+        source: io::Error,
+    },
+
+    /// The file's content is not valid
+    #[error("Invalid file `{path}`: {reason}")]
+    Invalid {
+        /// The path of the imported file
+        path: String,
+
+        /// The reason the file is not valid
+        reason: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cache::Cache,
+        shape_processor::{Error, ShapeProcessor},
+        BrepError, ImportError,
+    };
+
+    #[test]
+    fn missing_file_is_reported_before_tolerance() {
+        let processor = ShapeProcessor {
+            tolerance: None,
+            relative_tolerance: None,
+            angular_tolerance: None,
+            record_triangle_edge_checks: false,
+            cache: Cache::new(),
+        };
+        let shape = fj::Shape::from(fj::Import::from_path("missing.stl"));
+
+        let result = processor.process(&shape);
+
+        assert!(matches!(
+            result,
+            Err(Error::ToShape(BrepError::Import(ImportError::Io { .. })))
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+//! Import of STL files, in both the ASCII and the binary encoding
+//!
+//! Binary STL files can store the color of each triangle in its attribute
+//! bytes. The convention used by VisCAM and SolidView is supported: 5 bits per
+//! channel, blue in the lowest bits, and the highest bit set, if the color is
+//! valid.
+
+use std::str;
+
+use fj_interop::mesh::Color;
+use fj_math::Point;
+
+use super::Triangles;
+
+const HEADER_LEN: usize = 80;
+const TRIANGLE_LEN: usize = 50;
+
+pub fn read(data: &[u8]) -> Result<Triangles, String> {
+    // ASCII files start with `solid`, but some binary files do too. The size
+    // of a binary file is determined by its triangle count, which makes for a
+    // more reliable check.
+    if let Some(num_triangles) = binary_triangle_count(data) {
+        if data.len() == HEADER_LEN + 4 + num_triangles * TRIANGLE_LEN {
+            return Ok(read_binary(data, num_triangles));
+        }
+    }
+
+    let text = str::from_utf8(data)
+        .map_err(|_| "Neither a valid binary nor ASCII STL file".to_owned())?;
+    read_ascii(text)
+}
+
+fn binary_triangle_count(data: &[u8]) -> Option<usize> {
+    let bytes = data.get(HEADER_LEN..HEADER_LEN + 4)?;
+    let num_triangles = u32::from_le_bytes(bytes.try_into().ok()?);
+    num_triangles.try_into().ok()
+}
+
+fn read_binary(data: &[u8], num_triangles: usize) -> Triangles {
+    let triangles = &data[HEADER_LEN + 4..];
+
+    (0..num_triangles)
+        .map(|i| {
+            let triangle = &triangles[i * TRIANGLE_LEN..][..TRIANGLE_LEN];
+
+            let f32_at = |offset: usize| {
+                let bytes = [0, 1, 2, 3].map(|i| triangle[offset + i]);
+                f64::from(f32::from_le_bytes(bytes))
+            };
+
+            // The normal comes first. It's ignored, as it can be computed from
+            // the vertices.
+            let vertices = [12, 24, 36]
+                .map(|offset| [offset, offset + 4, offset + 8].map(f32_at));
+
+            let attribute = u16::from_le_bytes([triangle[48], triangle[49]]);
+
+            (vertices, attribute)
+        })
+        .filter(|(vertices, _)| is_finite(vertices))
+        .map(|(vertices, attribute)| {
+            (vertices.map(Point::from), decode_color(attribute))
+        })
+        .collect()
+}
+
+fn read_ascii(text: &str) -> Result<Triangles, String> {
+    let mut tokens = text.split_whitespace();
+    let mut vertices = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        // Everything apart from the vertices is structure, or the normal,
+        // which is ignored. Only the vertices are needed.
+        if token != "vertex" {
+            continue;
+        }
+
+        let mut coordinate = || {
+            let token = tokens
+                .next()
+                .ok_or_else(|| "Unexpected end of file".to_owned())?;
+            token
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid coordinate `{token}`"))
+        };
+
+        vertices.push([coordinate()?, coordinate()?, coordinate()?]);
+    }
+
+    if vertices.len() % 3 != 0 {
+        return Err("Number of vertices is not a multiple of 3".to_owned());
+    }
+
+    let triangles = vertices
+        .chunks(3)
+        .map(|vertices| [vertices[0], vertices[1], vertices[2]])
+        .filter(is_finite)
+        .map(|vertices| (vertices.map(Point::from), None))
+        .collect();
+
+    Ok(triangles)
+}
+
+fn decode_color(attribute: u16) -> Option<Color> {
+    if attribute & (1 << 15) == 0 {
+        return None;
+    }
+
+    let [r, g, b] = [10, 5, 0].map(|shift| {
+        let channel = ((attribute >> shift) & 0b11111) as u8;
+
+        // Fill the lower bits, so the full range of `u8` is used.
+        channel << 3 | channel >> 2
+    });
+
+    Some(Color([r, g, b, 255]))
+}
+
+fn is_finite(vertices: &[[f64; 3]; 3]) -> bool {
+    vertices.iter().flatten().all(|value| value.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_interop::mesh::Color;
+    use fj_math::Point;
+
+    #[test]
+    fn read_ascii() {
+        let stl = "solid test
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1e0 0 0
+      vertex 0 1.5 0
+    endloop
+  endfacet
+endsolid test
+";
+
+        let triangles = super::read(stl.as_bytes()).unwrap();
+
+        assert_eq!(triangles.len(), 1);
+        assert_eq!(triangles[0].0[2], Point::from([0., 1.5, 0.]));
+        assert_eq!(triangles[0].1, None);
+    }
+
+    #[test]
+    fn read_binary() {
+        // Start the header with `solid`, to make sure that doesn't confuse
+        // the detection of the encoding.
+        let mut stl = b"solid".to_vec();
+        stl.resize(80, 0);
+        stl.extend(1u32.to_le_bytes());
+
+        for value in [0., 0., 1., 0., 0., 0., 1., 0., 0., 0., 1., 0.] {
+            stl.extend((value as f32).to_le_bytes());
+        }
+        stl.extend(0b1_11111_00000_10000u16.to_le_bytes());
+
+        let triangles = super::read(&stl).unwrap();
+
+        assert_eq!(triangles.len(), 1);
+        assert_eq!(triangles[0].0[1], Point::from([1., 0., 0.]));
+        assert_eq!(triangles[0].1, Some(Color([255, 0, 132, 255])));
+    }
+}
@@ -0,0 +1,571 @@
+//! Import of 3MF (3D Manufacturing Format) files
+//!
+//! All objects that are part of the build are imported, with their transforms
+//! applied. Components of objects are resolved.
+//!
+//! Colors are taken from color groups of the materials extension, as well as
+//! from the base materials of the core specification. Coordinates are
+//! converted to millimeters.
+
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read},
+    str,
+};
+
+use fj_interop::mesh::Color;
+use fj_math::Point;
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+use zip::ZipArchive;
+
+use super::Triangles;
+
+/// The maximum depth of nested components
+///
+/// Guards against components that (directly or indirectly) refer to
+/// themselves.
+const MAX_DEPTH: usize = 32;
+
+pub fn read(data: &[u8]) -> Result<Triangles, String> {
+    let mut archive =
+        ZipArchive::new(Cursor::new(data)).map_err(|err| err.to_string())?;
+
+    // The relationships point to the model. Fall back to the conventional
+    // location, if they don't.
+    let model_path = read_file(&mut archive, "_rels/.rels")
+        .ok()
+        .and_then(|relationships| model_path(&relationships))
+        .unwrap_or_else(|| "3D/3dmodel.model".to_owned());
+
+    let model = read_file(&mut archive, &model_path)?;
+    read_model(&model)
+}
+
+fn read_file(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    path: &str,
+) -> Result<String, String> {
+    let mut file = archive
+        .by_name(path)
+        .map_err(|err| format!("Can't read `{path}`: {err}"))?;
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .map_err(|err| format!("Can't read `{path}`: {err}"))?;
+
+    Ok(content)
+}
+
+fn model_path(relationships: &str) -> Option<String> {
+    let tags = tags(relationships).ok()?;
+    tags.iter()
+        .filter(|tag| !tag.is_end && tag.name == "Relationship")
+        .find(|tag| {
+            tag.attribute("Type")
+                .map(|kind| kind.ends_with("/3dmodel"))
+                .unwrap_or(false)
+        })
+        .and_then(|tag| tag.attribute("Target"))
+        .map(|target| target.trim_start_matches('/').to_owned())
+}
+
+fn read_model(model: &str) -> Result<Triangles, String> {
+    let mut scale = 1.;
+    let mut property_groups = HashMap::new();
+    let mut objects = HashMap::new();
+    let mut items = Vec::new();
+
+    let mut current_group = None;
+    let mut current_object = None;
+
+    let tags = tags(model)?;
+    for tag in &tags {
+        if tag.is_end {
+            match tag.name.as_str() {
+                "colorgroup" | "basematerials" => current_group = None,
+                "object" => current_object = None,
+                _ => {}
+            }
+            continue;
+        }
+
+        match tag.name.as_str() {
+            "model" => {
+                scale = unit_in_millimeters(tag.attribute("unit"))?;
+            }
+            "colorgroup" | "basematerials" => {
+                let id = tag.required("id")?;
+                property_groups.insert(id.to_owned(), Vec::new());
+                current_group = Some(id);
+            }
+            "color" | "base" => {
+                let color = match tag.name.as_str() {
+                    "color" => tag.required("color")?,
+                    _ => tag.required("displaycolor")?,
+                };
+                let color = parse_color(color)?;
+
+                if let Some(group) =
+                    current_group.and_then(|id| property_groups.get_mut(id))
+                {
+                    group.push(color);
+                }
+            }
+            "object" => {
+                let id = tag.required("id")?;
+                let property = match tag.attribute("pid") {
+                    Some(pid) => Some((
+                        pid.to_owned(),
+                        tag.index("pindex")?.unwrap_or(0),
+                    )),
+                    None => None,
+                };
+
+                objects.insert(
+                    id.to_owned(),
+                    Object {
+                        property,
+                        ..Object::default()
+                    },
+                );
+                current_object = Some(id);
+            }
+            "vertex" | "triangle" | "component" => {
+                let object = current_object
+                    .and_then(|id| objects.get_mut(id))
+                    .ok_or_else(|| {
+                        format!("`{}` outside of object", tag.name)
+                    })?;
+
+                match tag.name.as_str() {
+                    "vertex" => {
+                        let [x, y, z] = ["x", "y", "z"]
+                            .map(|name| tag.number(name).transpose());
+                        object.vertices.push([
+                            x.ok_or("Missing coordinate")??,
+                            y.ok_or("Missing coordinate")??,
+                            z.ok_or("Missing coordinate")??,
+                        ]);
+                    }
+                    "triangle" => {
+                        let [v1, v2, v3] = ["v1", "v2", "v3"]
+                            .map(|name| tag.index(name).transpose());
+                        let property =
+                            match (tag.attribute("pid"), tag.index("p1")?) {
+                                (Some(pid), p1) => {
+                                    Some((pid.to_owned(), p1.unwrap_or(0)))
+                                }
+                                (None, Some(p1)) => object
+                                    .property
+                                    .as_ref()
+                                    .map(|(pid, _)| (pid.clone(), p1)),
+                                (None, None) => None,
+                            };
+
+                        object.triangles.push(Triangle {
+                            vertices: [
+                                v1.ok_or("Missing vertex index")??,
+                                v2.ok_or("Missing vertex index")??,
+                                v3.ok_or("Missing vertex index")??,
+                            ],
+                            property,
+                        });
+                    }
+                    _ => {
+                        let object_id = tag.required("objectid")?.to_owned();
+                        let transform =
+                            parse_transform(tag.attribute("transform"))?;
+                        object.components.push((object_id, transform));
+                    }
+                }
+            }
+            "item" => {
+                let object_id = tag.required("objectid")?.to_owned();
+                let transform = parse_transform(tag.attribute("transform"))?;
+                items.push((object_id, transform));
+            }
+            _ => {}
+        }
+    }
+
+    let resources = Resources {
+        property_groups,
+        objects,
+    };
+
+    let scaling = [scale, 0., 0., 0., scale, 0., 0., 0., scale, 0., 0., 0.];
+
+    let mut triangles = Vec::new();
+    for (object_id, transform) in items {
+        let transform = compose(&transform, &scaling);
+        resources.add_object(&object_id, &transform, 0, &mut triangles)?;
+    }
+
+    Ok(triangles)
+}
+
+struct Resources {
+    property_groups: HashMap<String, Vec<Color>>,
+    objects: HashMap<String, Object>,
+}
+
+impl Resources {
+    fn add_object(
+        &self,
+        id: &str,
+        transform: &Transform,
+        depth: usize,
+        triangles: &mut Triangles,
+    ) -> Result<(), String> {
+        if depth > MAX_DEPTH {
+            return Err("Components are nested too deeply".to_owned());
+        }
+
+        let object = self
+            .objects
+            .get(id)
+            .ok_or_else(|| format!("Reference to unknown object `{id}`"))?;
+
+        for triangle in &object.triangles {
+            let mut points = [Point::origin(); 3];
+            for (point, &index) in points.iter_mut().zip(&triangle.vertices) {
+                let vertex = object.vertices.get(index).ok_or_else(|| {
+                    format!("Invalid vertex index `{index}` in object `{id}`")
+                })?;
+                *point = apply(transform, *vertex)?;
+            }
+
+            let color = triangle
+                .property
+                .as_ref()
+                .or(object.property.as_ref())
+                .and_then(|(pid, index)| {
+                    self.property_groups.get(pid)?.get(*index).copied()
+                });
+
+            triangles.push((points, color));
+        }
+
+        for (component_id, component_transform) in &object.components {
+            let transform = compose(component_transform, transform);
+            self.add_object(component_id, &transform, depth + 1, triangles)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Object {
+    vertices: Vec<[f64; 3]>,
+    triangles: Vec<Triangle>,
+    components: Vec<(String, Transform)>,
+
+    /// The default property (group ID and index) of the object's triangles
+    property: Option<(String, usize)>,
+}
+
+struct Triangle {
+    vertices: [usize; 3],
+
+    /// The property (group ID and index) of the triangle
+    property: Option<(String, usize)>,
+}
+
+/// An affine transform, as defined by 3MF
+///
+/// The matrix is stored in row-major order, with the translation in the last
+/// row. Points are treated as row vectors, that are multiplied from the left.
+type Transform = [f64; 12];
+
+const IDENTITY: Transform = [1., 0., 0., 0., 1., 0., 0., 0., 1., 0., 0., 0.];
+
+fn parse_transform(transform: Option<&str>) -> Result<Transform, String> {
+    let transform = match transform {
+        Some(transform) => transform,
+        None => return Ok(IDENTITY),
+    };
+
+    let values = transform
+        .split_whitespace()
+        .map(|value| value.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("Invalid transform `{transform}`"))?;
+
+    values
+        .try_into()
+        .map_err(|_| format!("Invalid transform `{transform}`"))
+}
+
+fn apply(m: &Transform, [x, y, z]: [f64; 3]) -> Result<Point<3>, String> {
+    let point =
+        [0, 1, 2].map(|i| x * m[i] + y * m[3 + i] + z * m[6 + i] + m[9 + i]);
+
+    if point.iter().any(|value| !value.is_finite()) {
+        return Err("Vertex coordinates are not finite".to_owned());
+    }
+
+    Ok(Point::from(point))
+}
+
+/// Compose two transforms, such that `first` is applied before `second`
+fn compose(first: &Transform, second: &Transform) -> Transform {
+    let mut composed = [0.; 12];
+
+    for row in 0..4 {
+        for column in 0..3 {
+            let mut value = if row == 3 { second[9 + column] } else { 0. };
+            for k in 0..3 {
+                value += first[row * 3 + k] * second[k * 3 + column];
+            }
+            composed[row * 3 + column] = value;
+        }
+    }
+
+    composed
+}
+
+fn unit_in_millimeters(unit: Option<&str>) -> Result<f64, String> {
+    let scale = match unit.unwrap_or("millimeter") {
+        "micron" => 0.001,
+        "millimeter" => 1.,
+        "centimeter" => 10.,
+        "inch" => 25.4,
+        "foot" => 304.8,
+        "meter" => 1000.,
+        unit => return Err(format!("Unknown unit `{unit}`")),
+    };
+
+    Ok(scale)
+}
+
+fn parse_color(color: &str) -> Result<Color, String> {
+    let invalid = || format!("Invalid color `{color}`");
+
+    let hex = color.strip_prefix('#').ok_or_else(invalid)?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut channels = [255; 4];
+    for (i, channel) in channels.iter_mut().take(hex.len() / 2).enumerate() {
+        *channel = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| invalid())?;
+    }
+
+    Ok(Color(channels))
+}
+
+/// Read the tags of an XML document
+///
+/// Text content, comments, and declarations are ignored, as they aren't needed
+/// for 3MF. Empty elements result in a start tag, followed by an end tag.
+fn tags(xml: &str) -> Result<Vec<Tag>, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut buffer = Vec::new();
+    let mut tags = Vec::new();
+
+    loop {
+        let event = reader.read_event(&mut buffer).map_err(|err| {
+            format!(
+                "Invalid XML at position {}: {err}",
+                reader.buffer_position()
+            )
+        })?;
+
+        match event {
+            Event::Start(element) => {
+                tags.push(Tag::start(&reader, &element)?);
+            }
+            Event::Empty(element) => {
+                let tag = Tag::start(&reader, &element)?;
+                let name = tag.name.clone();
+
+                tags.push(tag);
+                tags.push(Tag::end(name));
+            }
+            Event::End(element) => {
+                tags.push(Tag::end(local_name(element.local_name())?));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        buffer.clear();
+    }
+
+    Ok(tags)
+}
+
+/// Decode the local name of an element
+///
+/// Callers pass the name without its namespace prefix. Elements from extensions
+/// have a prefix, but which one is used is up to the producer of the file.
+fn local_name(name: &[u8]) -> Result<String, String> {
+    str::from_utf8(name)
+        .map(str::to_owned)
+        .map_err(|_| "Element name is not valid UTF-8".to_owned())
+}
+
+struct Tag {
+    name: String,
+    attributes: Vec<(String, String)>,
+    is_end: bool,
+}
+
+impl Tag {
+    fn start(
+        reader: &Reader<&[u8]>,
+        element: &BytesStart,
+    ) -> Result<Self, String> {
+        let name = local_name(element.local_name())?;
+
+        let mut attributes = Vec::new();
+        for attribute in element.attributes() {
+            let invalid = |err: &dyn std::fmt::Display| -> String {
+                format!("Invalid attribute of `{name}`: {err}")
+            };
+
+            let attribute = attribute.map_err(|err| invalid(&err))?;
+            let key = str::from_utf8(attribute.key)
+                .map_err(|err| invalid(&err))?
+                .to_owned();
+            let value = attribute
+                .unescape_and_decode_value(reader)
+                .map_err(|err| invalid(&err))?;
+
+            attributes.push((key, value));
+        }
+
+        Ok(Self {
+            name,
+            attributes,
+            is_end: false,
+        })
+    }
+
+    fn end(name: String) -> Self {
+        Self {
+            name,
+            attributes: Vec::new(),
+            is_end: true,
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn required(&self, name: &str) -> Result<&str, String> {
+        self.attribute(name).ok_or_else(|| {
+            format!("Missing attribute `{name}` of `{}`", self.name)
+        })
+    }
+
+    fn number(&self, name: &str) -> Result<Option<f64>, String> {
+        self.parse(name)
+    }
+
+    fn index(&self, name: &str) -> Result<Option<usize>, String> {
+        self.parse(name)
+    }
+
+    fn parse<T: std::str::FromStr>(
+        &self,
+        name: &str,
+    ) -> Result<Option<T>, String> {
+        self.attribute(name)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    format!("Invalid value `{value}` of attribute `{name}`")
+                })
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_interop::mesh::Color;
+    use fj_math::Point;
+
+    #[test]
+    fn read_model() {
+        let model = r##"<?xml version="1.0" encoding="UTF-8"?>
+<model unit="centimeter" xmlns:m="http://schemas.microsoft.com/3dmanufacturing/material/2015/02">
+  <resources>
+    <m:colorgroup id="1">
+      <m:color color="#FF0000"/>
+      <m:color color="#0000FF80"/>
+    </m:colorgroup>
+    <object id="2" type="model" pid="1" pindex="0">
+      <mesh>
+        <vertices>
+          <vertex x="0" y="0" z="0"/>
+          <vertex x="1" y="0" z="0"/>
+          <vertex x="0" y="1" z="0"/>
+        </vertices>
+        <triangles>
+          <triangle v1="0" v2="1" v3="2"/>
+          <triangle v1="0" v2="2" v3="1" pid="1" p1="1"/>
+        </triangles>
+      </mesh>
+    </object>
+    <object id="3" type="model">
+      <components>
+        <component objectid="2" transform="1 0 0 0 1 0 0 0 1 0 0 5"/>
+      </components>
+    </object>
+  </resources>
+  <build>
+    <item objectid="3" transform="0 1 0 -1 0 0 0 0 1 0 0 0"/>
+  </build>
+</model>
+"##;
+
+        let triangles = super::read_model(model).unwrap();
+        assert_eq!(triangles.len(), 2);
+
+        // The component is moved up, then the item is rotated around the
+        // z-axis. Afterwards, everything is converted to millimeters.
+        let [_, b, _] = triangles[0].0;
+        assert_eq!(b, Point::from([0., 10., 50.]));
+
+        assert_eq!(triangles[0].1, Some(Color([255, 0, 0, 255])));
+        assert_eq!(triangles[1].1, Some(Color([0, 0, 255, 128])));
+    }
+
+    #[test]
+    fn read_model_with_empty_object() {
+        let model = r#"<model>
+  <resources>
+    <object id="1"/>
+    <vertex x="0" y="0" z="0"/>
+  </resources>
+</model>
+"#;
+
+        let err = super::read_model(model).err().unwrap();
+        assert_eq!(err, "`vertex` outside of object");
+    }
+
+    #[test]
+    fn model_path() {
+        let relationships = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Target="/3D/a&amp;b>c.model" Id="rel0" Type="http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel"/>
+</Relationships>
+"#;
+
+        assert_eq!(
+            super::model_path(relationships).as_deref(),
+            Some("3D/a&b>c.model")
+        );
+    }
+}
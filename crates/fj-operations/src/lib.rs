@@ -16,10 +16,12 @@
 
 #![warn(missing_docs)]
 
+pub mod cache;
 pub mod shape_processor;
 
 mod difference_2d;
 mod group;
+mod import;
 mod sketch;
 mod sweep;
 mod transform;
@@ -32,24 +34,36 @@ use fj_kernel::{
 };
 use fj_math::Aabb;
 
+pub use self::import::ImportError;
+
+use self::cache::Cache;
+
 /// Implemented for all operations from the [`fj`] crate
 pub trait Shape {
     /// The type that is used for the shape's boundary representation
     type Brep;
 
     /// Compute the boundary representation of the shape
+    ///
+    /// Implementations may use `cache` to reuse the results of previous
+    /// computations.
     fn compute_brep(
         &self,
         config: &ValidationConfig,
         tolerance: Tolerance,
+        cache: &Cache,
         debug_info: &mut DebugInfo,
-    ) -> Result<Validated<Self::Brep>, ValidationError>;
+    ) -> Result<Validated<Self::Brep>, BrepError>;
 
     /// Access the axis-aligned bounding box of a shape
     ///
     /// If a shape is empty, its [`Aabb`]'s `min` and `max` points must be equal
     /// (but are otherwise not specified).
-    fn bounding_volume(&self) -> Aabb<3>;
+    ///
+    /// Implementations may use `cache` to reuse data that is also needed to
+    /// compute the boundary representation. Fails, if that data can't be
+    /// loaded.
+    fn bounding_volume(&self, cache: &Cache) -> Result<Aabb<3>, BrepError>;
 }
 
 impl Shape for fj::Shape {
@@ -59,40 +73,45 @@ impl Shape for fj::Shape {
         &self,
         config: &ValidationConfig,
         tolerance: Tolerance,
+        cache: &Cache,
         debug_info: &mut DebugInfo,
-    ) -> Result<Validated<Self::Brep>, ValidationError> {
-        match self {
-            Self::Shape2d(shape) => validate(
+    ) -> Result<Validated<Self::Brep>, BrepError> {
+        cache.brep(self, tolerance, || match self {
+            Self::Shape2d(shape) => Ok(validate(
                 shape
-                    .compute_brep(config, tolerance, debug_info)?
+                    .compute_brep(config, tolerance, cache, debug_info)?
                     .into_inner()
                     .into_faces()
                     .collect(),
                 config,
-            ),
+            )?),
             Self::Group(shape) => {
-                shape.compute_brep(config, tolerance, debug_info)
+                shape.compute_brep(config, tolerance, cache, debug_info)
+            }
+            Self::Import(shape) => {
+                shape.compute_brep(config, tolerance, cache, debug_info)
             }
-            Self::Sweep(shape) => validate(
+            Self::Sweep(shape) => Ok(validate(
                 shape
-                    .compute_brep(config, tolerance, debug_info)?
+                    .compute_brep(config, tolerance, cache, debug_info)?
                     .into_inner()
                     .into_faces()
                     .collect(),
                 config,
-            ),
+            )?),
             Self::Transform(shape) => {
-                shape.compute_brep(config, tolerance, debug_info)
+                shape.compute_brep(config, tolerance, cache, debug_info)
             }
-        }
+        })
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
+    fn bounding_volume(&self, cache: &Cache) -> Result<Aabb<3>, BrepError> {
         match self {
-            Self::Shape2d(shape) => shape.bounding_volume(),
-            Self::Group(shape) => shape.bounding_volume(),
-            Self::Sweep(shape) => shape.bounding_volume(),
-            Self::Transform(shape) => shape.bounding_volume(),
+            Self::Shape2d(shape) => shape.bounding_volume(cache),
+            Self::Group(shape) => shape.bounding_volume(cache),
+            Self::Import(shape) => shape.bounding_volume(cache),
+            Self::Sweep(shape) => shape.bounding_volume(cache),
+            Self::Transform(shape) => shape.bounding_volume(cache),
         }
     }
 }
@@ -104,22 +123,73 @@ impl Shape for fj::Shape2d {
         &self,
         config: &ValidationConfig,
         tolerance: Tolerance,
+        cache: &Cache,
         debug_info: &mut DebugInfo,
-    ) -> Result<Validated<Self::Brep>, ValidationError> {
+    ) -> Result<Validated<Self::Brep>, BrepError> {
         match self {
             Self::Difference(shape) => {
-                shape.compute_brep(config, tolerance, debug_info)
+                shape.compute_brep(config, tolerance, cache, debug_info)
             }
             Self::Sketch(shape) => {
-                shape.compute_brep(config, tolerance, debug_info)
+                shape.compute_brep(config, tolerance, cache, debug_info)
             }
         }
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
+    fn bounding_volume(&self, cache: &Cache) -> Result<Aabb<3>, BrepError> {
         match self {
-            Self::Difference(shape) => shape.bounding_volume(),
-            Self::Sketch(shape) => shape.bounding_volume(),
+            Self::Difference(shape) => shape.bounding_volume(cache),
+            Self::Sketch(shape) => shape.bounding_volume(cache),
         }
     }
 }
+
+/// Compute the boundary representations of two independent shapes
+///
+/// If the `parallel` feature is enabled, both shapes are computed in parallel.
+/// The result is the same either way, including the order of the debug info.
+fn compute_brep_pair<A, B>(
+    a: &A,
+    b: &B,
+    config: &ValidationConfig,
+    tolerance: Tolerance,
+    cache: &Cache,
+    debug_info: &mut DebugInfo,
+) -> Result<(Validated<A::Brep>, Validated<B::Brep>), BrepError>
+where
+    A: Shape + Sync,
+    B: Shape + Sync,
+    A::Brep: Send,
+    B::Brep: Send,
+{
+    let mut debug_info_b = debug_info.branch();
+
+    #[cfg(feature = "parallel")]
+    let (a, b) = rayon::join(
+        || a.compute_brep(config, tolerance, cache, debug_info),
+        || b.compute_brep(config, tolerance, cache, &mut debug_info_b),
+    );
+    #[cfg(not(feature = "parallel"))]
+    let (a, b) = (
+        a.compute_brep(config, tolerance, cache, debug_info),
+        b.compute_brep(config, tolerance, cache, &mut debug_info_b),
+    );
+
+    debug_info.append(debug_info_b);
+
+    Ok((a?, b?))
+}
+
+/// An error that can occur while computing the boundary representation of a
+/// shape
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, thiserror::Error)]
+pub enum BrepError {
+    /// Validation of the boundary representation failed
+    #[error("Validation failed")]
+    Validation(#[from] ValidationError),
+
+    /// Importing a mesh from a file failed
+    #[error("Error importing mesh")]
+    Import(#[from] ImportError),
+}
@@ -1,65 +1,244 @@
 //! API for processing shapes
 
-use fj_interop::{debug::DebugInfo, processed_shape::ProcessedShape};
+use fj_interop::{bvh::Bvh, debug::DebugInfo, processed_shape::ProcessedShape};
 use fj_kernel::{
-    algorithms::{triangulate, InvalidTolerance, Tolerance},
-    validation::{ValidationConfig, ValidationError},
+    algorithms::{InvalidTolerance, Tolerance},
+    objects::Face,
+    validation::{Validated, ValidationConfig},
 };
 use fj_math::Scalar;
 
-use crate::Shape as _;
+use crate::{cache::Cache, BrepError, Shape as _};
+
+/// The relative tolerance used, if no tolerance is defined
+///
+/// See [`Tolerance::with_relative`].
+pub const DEFAULT_RELATIVE_TOLERANCE: f64 = 0.001;
 
 /// Processes an [`fj::Shape`] into a [`ProcessedShape`]
 pub struct ShapeProcessor {
     /// The tolerance value used for creating the triangle mesh
+    ///
+    /// If this is `None`, a default is computed from the size of the shape.
     pub tolerance: Option<Tolerance>,
+
+    /// The maximum deviation of each feature, relative to its size
+    ///
+    /// See [`Tolerance::with_relative`]. Overrides any relative tolerance
+    /// defined in `tolerance`. If neither defines one, a relative tolerance is
+    /// only applied to the default tolerance.
+    pub relative_tolerance: Option<Scalar>,
+
+    /// The maximum angular deviation of the approximation, in radians
+    ///
+    /// See [`Tolerance::with_angular`]. Overrides any angular tolerance defined
+    /// in `tolerance`.
+    pub angular_tolerance: Option<Scalar>,
+
+    /// Whether to record triangle edge checks during triangulation
+    ///
+    /// See [`DebugInfo::record_triangle_edge_checks`].
+    pub record_triangle_edge_checks: bool,
+
+    /// The cache used to reuse results between calls to `process`
+    ///
+    /// Parts of the shape that haven't changed since the last call are taken
+    /// from the cache, instead of being computed again.
+    pub cache: Cache,
 }
 
 impl ShapeProcessor {
     /// Process an [`fj::Shape`] into [`ProcessedShape`]
     pub fn process(&self, shape: &fj::Shape) -> Result<ProcessedShape, Error> {
-        let aabb = shape.bounding_volume();
+        let tolerance = self.tolerance(shape)?;
+        self.cache.next_generation();
 
-        let tolerance = match self.tolerance {
+        self.process_with_tolerance(shape, tolerance)
+    }
+
+    /// Process each part of an [`fj::Shape`] into a separate [`ProcessedShape`]
+    ///
+    /// The parts of a shape are the members of its top-level groups. A shape
+    /// that is not a group consists of a single part. All parts are processed
+    /// with the same tolerance, as if the whole shape was processed at once.
+    pub fn process_parts(
+        &self,
+        shape: &fj::Shape,
+    ) -> Result<Vec<ProcessedShape>, Error> {
+        let tolerance = self.tolerance(shape)?;
+        self.cache.next_generation();
+
+        let mut parts = Vec::new();
+        collect_parts(shape, &mut parts);
+
+        parts
+            .into_iter()
+            .map(|part| self.process_with_tolerance(part, tolerance))
+            .collect()
+    }
+
+    /// Process an [`fj::Shape`] into its validated boundary representation
+    ///
+    /// This stops short of triangulating the shape, which makes it suitable
+    /// for exporting to file formats that store exact geometry.
+    pub fn process_brep(
+        &self,
+        shape: &fj::Shape,
+    ) -> Result<Validated<Vec<Face>>, Error> {
+        let tolerance = self.tolerance(shape)?;
+        self.cache.next_generation();
+
+        let config = ValidationConfig::default();
+        let mut debug_info = DebugInfo::new();
+        let faces = shape.compute_brep(
+            &config,
+            tolerance,
+            &self.cache,
+            &mut debug_info,
+        )?;
+
+        Ok(faces)
+    }
+
+    fn tolerance(&self, shape: &fj::Shape) -> Result<Tolerance, Error> {
+        let mut tolerance = match self.tolerance {
             None => {
-                // Compute a reasonable default for the tolerance value. To do
-                // this, we just look at the smallest non-zero extent of the
-                // bounding box and divide that by some value.
-                let mut min_extent = Scalar::MAX;
-                for extent in aabb.size().components {
-                    if extent > Scalar::ZERO && extent < min_extent {
-                        min_extent = extent;
-                    }
-                }
-
-                let tolerance = min_extent / Scalar::from_f64(1000.);
+                // Compute a reasonable default for the tolerance value. The
+                // deviation of each feature is limited in relation to its
+                // size, so small features in large models keep their shape.
+                //
+                // The absolute tolerance is only an upper bound then. We
+                // derive it from the largest extent of the bounding box, so
+                // one small extent (like the thickness of a plate) doesn't
+                // force a fine approximation onto the large features.
+                let max_extent = shape
+                    .bounding_volume(&self.cache)?
+                    .size()
+                    .components
+                    .into_iter()
+                    .fold(Scalar::ZERO, Scalar::max);
+
+                let tolerance = max_extent / Scalar::from_f64(1000.);
                 Tolerance::from_scalar(tolerance)?
+                    .with_relative(DEFAULT_RELATIVE_TOLERANCE)?
             }
             Some(user_defined_tolerance) => user_defined_tolerance,
         };
 
+        if let Some(relative) = self.relative_tolerance {
+            tolerance = tolerance
+                .with_relative(relative)
+                .map_err(Error::Tolerance)?;
+        }
+        if let Some(angular) = self.angular_tolerance {
+            tolerance =
+                tolerance.with_angular(angular).map_err(Error::Tolerance)?;
+        }
+
+        Ok(tolerance)
+    }
+
+    fn process_with_tolerance(
+        &self,
+        shape: &fj::Shape,
+        tolerance: Tolerance,
+    ) -> Result<ProcessedShape, Error> {
+        let aabb = shape.bounding_volume(&self.cache)?;
+
         let config = ValidationConfig::default();
-        let mut debug_info = DebugInfo::new();
-        let shape = shape.compute_brep(&config, tolerance, &mut debug_info)?;
-        let mesh = triangulate(shape.into_inner(), tolerance, &mut debug_info);
+        let mut debug_info = DebugInfo {
+            record_triangle_edge_checks: self.record_triangle_edge_checks,
+            ..DebugInfo::new()
+        };
+        let shape = shape.compute_brep(
+            &config,
+            tolerance,
+            &self.cache,
+            &mut debug_info,
+        )?;
+        let mesh = self.cache.triangulate(
+            shape.into_inner(),
+            tolerance,
+            &mut debug_info,
+        );
+        let bvh = Bvh::new(&mesh);
 
         Ok(ProcessedShape {
             aabb,
             mesh,
+            bvh,
             debug_info,
         })
     }
 }
 
+fn collect_parts<'r>(shape: &'r fj::Shape, parts: &mut Vec<&'r fj::Shape>) {
+    match shape {
+        fj::Shape::Group(group) => {
+            collect_parts(&group.a, parts);
+            collect_parts(&group.b, parts);
+        }
+        shape => parts.push(shape),
+    }
+}
+
 /// A shape processing error
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     /// Error converting to shape
     #[error("Error converting to shape")]
-    ToShape(#[from] ValidationError),
+    ToShape(#[from] BrepError),
 
     /// Model has zero size
     #[error("Model has zero size")]
     Extent(#[from] InvalidTolerance),
+
+    /// Invalid relative or angular tolerance
+    #[error("Invalid relative or angular tolerance")]
+    Tolerance(#[source] InvalidTolerance),
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_math::Scalar;
+
+    use crate::cache::Cache;
+
+    use super::{ShapeProcessor, DEFAULT_RELATIVE_TOLERANCE};
+
+    #[test]
+    fn default_tolerance_is_relative_to_feature_size() {
+        let processor = ShapeProcessor {
+            tolerance: None,
+            relative_tolerance: None,
+            angular_tolerance: None,
+            record_triangle_edge_checks: false,
+            cache: Cache::new(),
+        };
+        let shape = fj::Shape::from(fj::Sketch::from_points(vec![
+            [0., 0.],
+            [100., 0.],
+            [100., 2.],
+            [0., 2.],
+        ]));
+
+        let tolerance = processor.tolerance(&shape).unwrap();
+
+        assert_eq!(tolerance.inner(), Scalar::from_f64(0.1));
+        assert_eq!(
+            tolerance.relative(),
+            Some(Scalar::from_f64(DEFAULT_RELATIVE_TOLERANCE))
+        );
+
+        // Small features are approximated more closely than large ones.
+        assert_eq!(
+            tolerance.for_feature_size(Scalar::from_f64(1.)),
+            Scalar::from_f64(0.001)
+        );
+        assert_eq!(
+            tolerance.for_feature_size(Scalar::from_f64(1000.)),
+            Scalar::from_f64(0.1)
+        );
+    }
 }
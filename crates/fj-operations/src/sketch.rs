@@ -2,11 +2,11 @@ use fj_interop::{debug::DebugInfo, mesh::Color};
 use fj_kernel::{
     algorithms::Tolerance,
     objects::{Cycle, Edge, Face, Sketch, Surface},
-    validation::{validate, Validated, ValidationConfig, ValidationError},
+    validation::{validate, Validated, ValidationConfig},
 };
 use fj_math::{Aabb, Point, Scalar};
 
-use super::Shape;
+use super::{BrepError, Cache, Shape};
 
 impl Shape for fj::Sketch {
     type Brep = Sketch;
@@ -15,8 +15,9 @@ impl Shape for fj::Sketch {
         &self,
         config: &ValidationConfig,
         _: Tolerance,
+        _: &Cache,
         _: &mut DebugInfo,
-    ) -> Result<Validated<Self::Brep>, ValidationError> {
+    ) -> Result<Validated<Self::Brep>, BrepError> {
         let surface = Surface::xy_plane();
 
         let face = match self.chain() {
@@ -44,11 +45,11 @@ impl Shape for fj::Sketch {
         };
 
         let sketch = Sketch::new().with_faces([face]);
-        validate(sketch, config)
+        Ok(validate(sketch, config)?)
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
-        match self.chain() {
+    fn bounding_volume(&self, _: &Cache) -> Result<Aabb<3>, BrepError> {
+        let aabb = match self.chain() {
             fj::Chain::Circle(circle) => Aabb {
                 min: Point::from([-circle.radius(), -circle.radius(), 0.0]),
                 max: Point::from([circle.radius(), circle.radius(), 0.0]),
@@ -60,6 +61,8 @@ impl Shape for fj::Sketch {
                     .map(Point::from)
                     .map(Point::to_xyz),
             ),
-        }
+        };
+
+        Ok(aabb)
     }
 }
@@ -2,11 +2,11 @@ use fj_interop::{debug::DebugInfo, mesh::Color};
 use fj_kernel::{
     algorithms::{sweep, Tolerance},
     objects::Solid,
-    validation::{validate, Validated, ValidationConfig, ValidationError},
+    validation::{validate, Validated, ValidationConfig},
 };
 use fj_math::{Aabb, Vector};
 
-use super::Shape;
+use super::{BrepError, Cache, Shape};
 
 impl Shape for fj::Sweep {
     type Brep = Solid;
@@ -15,25 +15,24 @@ impl Shape for fj::Sweep {
         &self,
         config: &ValidationConfig,
         tolerance: Tolerance,
+        cache: &Cache,
         debug_info: &mut DebugInfo,
-    ) -> Result<Validated<Self::Brep>, ValidationError> {
-        let sketch =
-            self.shape().compute_brep(config, tolerance, debug_info)?;
+    ) -> Result<Validated<Self::Brep>, BrepError> {
+        let sketch = self
+            .shape()
+            .compute_brep(config, tolerance, cache, debug_info)?;
         let path = Vector::from(self.path());
         let color = self.shape().color();
 
         let solid = sweep(sketch.into_inner(), path, tolerance, Color(color));
-        validate(solid, config)
+        Ok(validate(solid, config)?)
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
-        self.shape()
-            .bounding_volume()
-            .merged(&Aabb::<3>::from_points(
-                self.shape()
-                    .bounding_volume()
-                    .vertices()
-                    .map(|v| v + self.path()),
-            ))
+    fn bounding_volume(&self, cache: &Cache) -> Result<Aabb<3>, BrepError> {
+        let aabb = self.shape().bounding_volume(cache)?;
+
+        Ok(aabb.merged(&Aabb::<3>::from_points(
+            aabb.vertices().map(|v| v + self.path()),
+        )))
     }
 }
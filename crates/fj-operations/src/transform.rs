@@ -2,11 +2,11 @@ use fj_interop::debug::DebugInfo;
 use fj_kernel::{
     algorithms::{transform_faces, Tolerance},
     objects::Face,
-    validation::{validate, Validated, ValidationConfig, ValidationError},
+    validation::{validate, Validated, ValidationConfig},
 };
 use fj_math::{Aabb, Transform, Vector};
 
-use super::Shape;
+use super::{BrepError, Cache, Shape};
 
 impl Shape for fj::Transform {
     type Brep = Vec<Face>;
@@ -15,20 +15,22 @@ impl Shape for fj::Transform {
         &self,
         config: &ValidationConfig,
         tolerance: Tolerance,
+        cache: &Cache,
         debug_info: &mut DebugInfo,
-    ) -> Result<Validated<Self::Brep>, ValidationError> {
+    ) -> Result<Validated<Self::Brep>, BrepError> {
         let mut faces = self
             .shape
-            .compute_brep(config, tolerance, debug_info)?
+            .compute_brep(config, tolerance, cache, debug_info)?
             .into_inner();
 
         transform_faces(&mut faces, &make_transform(self));
 
-        validate(faces, config)
+        Ok(validate(faces, config)?)
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
-        make_transform(self).transform_aabb(&self.shape.bounding_volume())
+    fn bounding_volume(&self, cache: &Cache) -> Result<Aabb<3>, BrepError> {
+        let aabb = self.shape.bounding_volume(cache)?;
+        Ok(make_transform(self).transform_aabb(&aabb))
     }
 }
 
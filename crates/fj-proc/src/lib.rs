@@ -1,5 +1,5 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens as _};
 use syn::{
     bracketed, parenthesized, parse::Parse, parse_macro_input, parse_quote,
 };
@@ -71,6 +71,22 @@ use syn::{
 ///     spacer.into()
 /// }
 /// ```
+///
+/// The parameters, their constraints, and the doc comments of the function
+/// and its arguments are made available to the host application. This allows
+/// the application to present the parameters to the user.
+///
+/// ```rust
+/// /// A cuboid
+/// #[fj::model]
+/// pub fn cuboid(
+///     /// The length of the cuboid along the x-axis
+///     #[param(default = 3.0, min = 0.1)]
+///     x: f64,
+/// ) -> fj::Shape {
+///     todo!()
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn model(_: TokenStream, input: TokenStream) -> TokenStream {
     let item = parse_macro_input!(input as syn::ItemFn);
@@ -84,19 +100,24 @@ pub fn model(_: TokenStream, input: TokenStream) -> TokenStream {
     let mut min_checks = Vec::new();
     let mut max_checks = Vec::new();
 
-    for Argument { attr, ident, ty } in &args {
+    for arg in &args {
+        let (attr, ident, ty) = (&arg.attr, &arg.ident, &arg.ty);
         if let Some(attr) = attr {
             if let Some(default) = attr.get_default() {
                 let def = default.val;
                 parameter_extraction.push(quote! {
                     let #ident: #ty = args.get(stringify!(#ident))
-                            .map(|arg| arg.parse().unwrap())
+                            .map(|arg| arg.parse().unwrap_or_else(|err| {
+                                panic!("Invalid value for `{}`: {:?}", stringify!(#ident), err)
+                            }))
                             .unwrap_or(#def);
                 });
             } else {
                 parameter_extraction.push(quote! {
                 let #ident: #ty = args.get(stringify!(#ident))
-                        .map(|arg| arg.parse().unwrap())
+                        .map(|arg| arg.parse().unwrap_or_else(|err| {
+                                panic!("Invalid value for `{}`: {:?}", stringify!(#ident), err)
+                            }))
                         .expect(format!("A value for `{}` has to be provided since no default is specified",stringify!(#ident)).as_str());
             });
             }
@@ -120,17 +141,72 @@ pub fn model(_: TokenStream, input: TokenStream) -> TokenStream {
         } else {
             parameter_extraction.push(quote! {
                 let #ident: #ty = args.get(stringify!(#ident))
-                        .map(|arg| arg.parse().unwrap())
+                        .map(|arg| arg.parse().unwrap_or_else(|err| {
+                                panic!("Invalid value for `{}`: {:?}", stringify!(#ident), err)
+                            }))
                         .expect(format!("A value for `{}` has to be provided since no default is specified",stringify!(#ident)).as_str());
             });
         }
     }
 
+    // The host checks the version before calling `model`, as the layout of the
+    // types passed between them might differ between versions.
+    let version_boilerplate = quote! {
+        #[no_mangle]
+        pub extern "C" fn fj_version() -> fj::abi::Version {
+            fj::abi::Version::current()
+        }
+    };
+
+    let parameter_metadata = args.iter().map(|arg| {
+        let attr = arg.attr.as_ref();
+        let expression = |param: Option<DefaultParam>| {
+            optional_string(
+                param.map(|param| param.val.to_token_stream().to_string()),
+            )
+        };
+
+        let name = arg.ident.to_string();
+        let ty = arg.ty.to_string();
+        let default = expression(attr.and_then(HelperAttribute::get_default));
+        let min = expression(attr.and_then(HelperAttribute::get_minimum));
+        let max = expression(attr.and_then(HelperAttribute::get_maximum));
+        let description = optional_string(arg.doc.clone());
+
+        quote! {
+            fj::ParameterMetadata {
+                name: String::from(#name),
+                ty: String::from(#ty),
+                default: #default,
+                min: #min,
+                max: #max,
+                description: #description,
+            }
+        }
+    });
+
+    let model_name = item.sig.ident.to_string();
+    let model_description = optional_string(doc_comment(&item.attrs));
+
+    // Describes the model's parameters to the host. The model function itself
+    // isn't called, so this can't panic.
+    let metadata_boilerplate = quote! {
+        #[no_mangle]
+        pub extern "C" fn model_metadata() -> fj::abi::ModelMetadata {
+            fj::ModelMetadata {
+                name: String::from(#model_name),
+                description: #model_description,
+                parameters: vec![#( #parameter_metadata ),*],
+            }
+            .into()
+        }
+    };
+
     let function_boilerplate = quote! {
         #[no_mangle]
             pub extern "C" fn model(
                 args: &std::collections::HashMap<String, String>
-            ) -> fj::Shape
+            ) -> fj::abi::ModelResult
     };
 
     let function_name = &item.sig.ident;
@@ -139,31 +215,42 @@ pub fn model(_: TokenStream, input: TokenStream) -> TokenStream {
     let arg_types: Vec<_> = args.iter().map(|a| &a.ty).collect();
     let return_type = &item.sig.output;
 
+    // Panics must not unwind across the `extern "C"` boundary. They are caught
+    // and passed to the host as a value instead.
     quote! {
+        #version_boilerplate
+
+        #metadata_boilerplate
+
         #function_boilerplate {
-            #(
-                #parameter_extraction
-            )*
-            #(
-                #min_checks
-            )*
-            #(
-                #max_checks
-            )*
-
-            fn #function_name(
-                #( #arg_names : #arg_types ),*
-            ) #return_type {
-                #body
-            }
+            fj::abi::catch_panic(|| {
+                #(
+                    #parameter_extraction
+                )*
+                #(
+                    #min_checks
+                )*
+                #(
+                    #max_checks
+                )*
+
+                fn #function_name(
+                    #( #arg_names : #arg_types ),*
+                ) #return_type {
+                    #body
+                }
 
-            #function_name(#( #arg_names),*).into()
+                #function_name(#( #arg_names),*).into()
+            })
         }
     }
     .into()
 }
 
 /// Represents one parameter given to the `model`
+///
+/// The parameter can also have a doc comment, which is not shown here.
+///
 /// `#[param(default=3, min=4)] num_points: u64`
 /// `^^^^^^^^^^^^^^^^^^^^^^^^^^ ~~~~~~~~~~  ^^^-- ty`
 /// `           |                    |`
@@ -171,6 +258,7 @@ pub fn model(_: TokenStream, input: TokenStream) -> TokenStream {
 #[derive(Debug, Clone)]
 struct Argument {
     pub attr: Option<HelperAttribute>,
+    pub doc: Option<String>,
     pub ident: proc_macro2::Ident,
     pub ty: proc_macro2::Ident,
 }
@@ -178,15 +266,63 @@ struct Argument {
 impl Parse for Argument {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut attr = None;
-        if input.peek(syn::token::Pound) {
-            attr = Some(input.parse()?);
+        let mut doc_attrs = Vec::new();
+        for attribute in input.call(syn::Attribute::parse_outer)? {
+            if attribute.path.is_ident("doc") {
+                doc_attrs.push(attribute);
+            } else {
+                attr = Some(syn::parse2(attribute.to_token_stream())?);
+            }
         }
+        let doc = doc_comment(&doc_attrs);
+
         let ident: proc_macro2::Ident = input.parse()?;
 
         let _: syn::token::Colon = input.parse()?;
 
         let ty: proc_macro2::Ident = input.parse()?;
-        Ok(Self { attr, ident, ty })
+        Ok(Self {
+            attr,
+            doc,
+            ident,
+            ty,
+        })
+    }
+}
+
+/// Extract the doc comment from a list of attributes
+///
+/// Returns `None`, if there is no doc comment.
+fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(line),
+                ..
+            })) => Some(line.value()),
+            _ => None,
+        })
+        .map(|line| {
+            // Doc comments are written with a space after the `///`.
+            line.strip_prefix(' ').map(str::to_owned).unwrap_or(line)
+        })
+        .collect::<Vec<_>>();
+
+    let doc = lines.join("\n").trim().to_owned();
+    if doc.is_empty() {
+        return None;
+    }
+
+    Some(doc)
+}
+
+/// Generate an expression that evaluates to the given `Option<String>`
+fn optional_string(value: Option<String>) -> proc_macro2::TokenStream {
+    match value {
+        Some(value) => quote! { Some(String::from(#value)) },
+        None => quote! { None },
     }
 }
 
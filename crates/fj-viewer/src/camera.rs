@@ -137,22 +137,9 @@ impl Camera {
         let cursor = self.cursor_to_model_space(cursor?);
         let dir = (cursor - origin).normalize();
 
-        let mut min_t = None;
-
-        for triangle in shape.mesh.triangles() {
-            let t =
-                triangle
-                    .inner
-                    .cast_local_ray(origin, dir, f64::INFINITY, true);
-
-            if let Some(t) = t {
-                if t <= min_t.unwrap_or(t) {
-                    min_t = Some(t);
-                }
-            }
-        }
+        let hit = shape.bvh.cast_ray(origin, dir, f64::INFINITY)?;
 
-        Some(FocusPoint(origin + dir * min_t?))
+        Some(FocusPoint(hit.point))
     }
 
     /// Access the transform from camera to model space.
@@ -4,6 +4,7 @@ mod config_ui;
 mod draw_config;
 mod drawables;
 mod geometries;
+mod model_parameters;
 mod pipelines;
 mod renderer;
 mod shaders;
@@ -13,6 +14,7 @@ mod vertices;
 
 pub use self::{
     draw_config::DrawConfig,
+    model_parameters::{ModelParameter, ModelParameters},
     renderer::{DrawError, InitError, Renderer},
 };
 
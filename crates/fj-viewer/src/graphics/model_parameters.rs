@@ -0,0 +1,184 @@
+//! Editing the parameters of the active model
+
+use std::mem;
+
+/// The parameters of the active model, as edited in the UI
+#[derive(Debug, Default)]
+pub struct ModelParameters {
+    parameters: Vec<ModelParameter>,
+
+    /// Indicates that a parameter has been edited
+    changed: bool,
+
+    /// The parameter whose text is being edited, if any
+    ///
+    /// Text is only applied once editing has finished, as the model can't do
+    /// anything useful with partial input.
+    editing: Option<usize>,
+}
+
+impl ModelParameters {
+    /// Replace the parameters, for example after the model has been rebuilt
+    pub fn update(&mut self, parameters: Vec<ModelParameter>) {
+        self.parameters = parameters;
+        self.editing = None;
+
+        // Expressions are converted to strings token by token, which puts
+        // spaces into negative numbers.
+        for parameter in &mut self.parameters {
+            if parameter.is_numeric() {
+                if let Some(value) = parse_number(&parameter.value) {
+                    parameter.value = format_number(value, parameter);
+                }
+            }
+        }
+    }
+
+    /// Indicate whether there are any parameters
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+
+    /// Iterate over the names and values of the parameters
+    pub fn values(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.parameters.iter().map(|parameter| {
+            (parameter.name.as_str(), parameter.value.as_str())
+        })
+    }
+
+    /// Indicate whether a parameter has been edited
+    ///
+    /// Resets the flag, so each edit is only reported once.
+    pub fn take_changed(&mut self) -> bool {
+        mem::take(&mut self.changed)
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        for (i, parameter) in self.parameters.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let label = ui.label(&parameter.name);
+                if let Some(description) = &parameter.description {
+                    label.on_hover_text(description);
+                }
+
+                let number = parse_number(&parameter.value)
+                    .filter(|_| parameter.is_numeric());
+                let mut number = match number {
+                    Some(number) => number,
+                    None => {
+                        let response =
+                            ui.text_edit_singleline(&mut parameter.value);
+
+                        if response.changed() {
+                            self.editing = Some(i);
+                        }
+                        if response.lost_focus() && self.editing == Some(i) {
+                            self.editing = None;
+                            self.changed = true;
+                        }
+
+                        return;
+                    }
+                };
+
+                let min = parameter.min.as_deref().and_then(parse_number);
+                let max = parameter.max.as_deref().and_then(parse_number);
+                let min = match min {
+                    None if parameter.is_unsigned() => Some(0.),
+                    min => min,
+                };
+
+                let response = match (min, max) {
+                    (Some(min), Some(max)) => {
+                        let mut slider =
+                            egui::Slider::new(&mut number, min..=max);
+                        if parameter.is_integer() {
+                            slider = slider.step_by(1.).fixed_decimals(0);
+                        }
+                        ui.add(slider)
+                    }
+                    (min, max) => {
+                        let min = min.unwrap_or(f64::NEG_INFINITY);
+                        let max = max.unwrap_or(f64::INFINITY);
+
+                        let mut drag_value = egui::DragValue::new(&mut number)
+                            .clamp_range(min..=max);
+                        drag_value = if parameter.is_integer() {
+                            drag_value.speed(1.).fixed_decimals(0)
+                        } else {
+                            drag_value.speed(0.01)
+                        };
+                        ui.add(drag_value)
+                    }
+                };
+
+                if response.changed() {
+                    parameter.value = format_number(number, parameter);
+                    self.changed = true;
+                }
+            });
+        }
+    }
+}
+
+/// A parameter of the active model
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelParameter {
+    /// The name of the parameter
+    pub name: String,
+
+    /// The Rust type of the parameter (e.g. `f64`)
+    pub ty: String,
+
+    /// The current value
+    pub value: String,
+
+    /// The minimum value, as defined by the model
+    ///
+    /// Only taken into account, if it is a number. Limits that are defined
+    /// using other parameters are ignored.
+    pub min: Option<String>,
+
+    /// The maximum value, as defined by the model
+    ///
+    /// Only taken into account, if it is a number. Limits that are defined
+    /// using other parameters are ignored.
+    pub max: Option<String>,
+
+    /// A description of the parameter, if any
+    pub description: Option<String>,
+}
+
+impl ModelParameter {
+    fn is_numeric(&self) -> bool {
+        self.is_integer() || matches!(self.ty.as_str(), "f32" | "f64")
+    }
+
+    fn is_integer(&self) -> bool {
+        self.is_unsigned()
+            || matches!(
+                self.ty.as_str(),
+                "i8" | "i16" | "i32" | "i64" | "i128" | "isize"
+            )
+    }
+
+    fn is_unsigned(&self) -> bool {
+        matches!(
+            self.ty.as_str(),
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+        )
+    }
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    let value = value.split_whitespace().collect::<String>();
+    value.parse().ok().filter(|value: &f64| value.is_finite())
+}
+
+fn format_number(value: f64, parameter: &ModelParameter) -> String {
+    if parameter.is_integer() {
+        format!("{:.0}", value)
+    } else {
+        value.to_string()
+    }
+}
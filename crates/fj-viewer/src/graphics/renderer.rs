@@ -13,8 +13,9 @@ use crate::{
 
 use super::{
     config_ui::ConfigUi, draw_config::DrawConfig, drawables::Drawables,
-    geometries::Geometries, pipelines::Pipelines, transform::Transform,
-    uniforms::Uniforms, vertices::Vertices, DEPTH_FORMAT,
+    geometries::Geometries, model_parameters::ModelParameters,
+    pipelines::Pipelines, transform::Transform, uniforms::Uniforms,
+    vertices::Vertices, DEPTH_FORMAT,
 };
 
 #[derive(Default)]
@@ -58,6 +59,9 @@ pub struct Renderer {
 
     config_ui: ConfigUi,
 
+    status: String,
+    errors: Vec<String>,
+
     /// State required for integration with `egui`.
     pub egui: EguiState,
 }
@@ -253,6 +257,9 @@ impl Renderer {
 
             config_ui,
 
+            status: String::new(),
+            errors: Vec::new(),
+
             egui: EguiState {
                 context: egui_context,
                 winit_state: egui_winit_state,
@@ -272,6 +279,19 @@ impl Renderer {
         self.geometries = Geometries::new(&self.device, &mesh, &lines, aabb);
     }
 
+    /// Updates the status of the model, as displayed in the UI
+    pub fn update_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    /// Updates the errors that are displayed in the UI
+    ///
+    /// Each error is displayed as-is, in a monospace font. Pass an empty list to
+    /// hide the error panel.
+    pub fn update_errors(&mut self, errors: Vec<String>) {
+        self.errors = errors;
+    }
+
     /// Resizes the render surface.
     ///
     /// # Arguments
@@ -288,24 +308,34 @@ impl Renderer {
     }
 
     /// Draws the renderer, camera, and config state to the window.
+    ///
+    /// The model is only drawn, if a camera is provided. The UI is always
+    /// drawn, so the status and any errors are visible before the model has
+    /// been loaded for the first time.
+    ///
+    /// The model's parameters can be edited in the UI. Use
+    /// [`ModelParameters::take_changed`] to find out whether that happened.
     pub fn draw(
         &mut self,
-        camera: &Camera,
+        camera: Option<&Camera>,
         config: &mut DrawConfig,
+        parameters: &mut ModelParameters,
         window: &egui_winit::winit::window::Window,
     ) -> Result<(), DrawError> {
-        let aspect_ratio = self.surface_config.width as f64
-            / self.surface_config.height as f64;
-        let uniforms = Uniforms {
-            transform: Transform::for_vertices(camera, aspect_ratio),
-            transform_normals: Transform::for_normals(camera),
-        };
-
-        self.queue.write_buffer(
-            &self.uniform_buffer,
-            0,
-            bytemuck::cast_slice(&[uniforms]),
-        );
+        if let Some(camera) = camera {
+            let aspect_ratio = self.surface_config.width as f64
+                / self.surface_config.height as f64;
+            let uniforms = Uniforms {
+                transform: Transform::for_vertices(camera, aspect_ratio),
+                transform_normals: Transform::for_normals(camera),
+            };
+
+            self.queue.write_buffer(
+                &self.uniform_buffer,
+                0,
+                bytemuck::cast_slice(&[uniforms]),
+            );
+        }
 
         let surface_texture = self.surface.get_current_texture()?;
         let color_view = surface_texture
@@ -319,8 +349,9 @@ impl Renderer {
         self.clear_views(&mut encoder, &color_view);
 
         let drawables = Drawables::new(&self.geometries, &self.pipelines);
+        let draw_geometry = camera.is_some();
 
-        if config.draw_model {
+        if draw_geometry && config.draw_model {
             drawables.model.draw(
                 &mut encoder,
                 &color_view,
@@ -328,7 +359,7 @@ impl Renderer {
                 &self.bind_group,
             );
         }
-        if config.draw_mesh {
+        if draw_geometry && config.draw_mesh {
             drawables.mesh.draw(
                 &mut encoder,
                 &color_view,
@@ -336,7 +367,7 @@ impl Renderer {
                 &self.bind_group,
             );
         }
-        if config.draw_debug {
+        if draw_geometry && config.draw_debug {
             drawables.lines.draw(
                 &mut encoder,
                 &color_view,
@@ -345,7 +376,7 @@ impl Renderer {
             );
         }
 
-        if self.egui.options.show_original_ui {
+        if draw_geometry && self.egui.options.show_original_ui {
             self.config_ui
                 .draw(
                     &self.device,
@@ -411,6 +442,19 @@ impl Renderer {
         egui::SidePanel::left("fj-left-panel").show(&self.egui.context, |ui| {
             ui.add_space(16.0);
 
+            if !self.status.is_empty() {
+                ui.label(&self.status);
+                ui.add_space(16.0);
+            }
+
+            if !parameters.is_empty() {
+                ui.group(|ui| {
+                    ui.strong("Model parameters");
+                    parameters.ui(ui);
+                });
+                ui.add_space(16.0);
+            }
+
             ui.group(|ui| {
                 ui.checkbox(&mut config.draw_model, "Render model")
                     .on_hover_text_at_pointer("Toggle with 1");
@@ -545,6 +589,22 @@ impl Renderer {
             ui.add_space(16.0);
         });
 
+        if !self.errors.is_empty() {
+            egui::TopBottomPanel::bottom("fj-error-panel")
+                .resizable(true)
+                .show(&self.egui.context, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for error in &self.errors {
+                            ui.label(
+                                egui::RichText::new(error)
+                                    .monospace()
+                                    .color(egui::Color32::LIGHT_RED),
+                            );
+                        }
+                    });
+                });
+        }
+
         // End the UI frame. We could now handle the output and draw the UI with the backend.
         let egui_output = self.egui.context.end_frame();
         let egui_paint_jobs = self.egui.context.tessellate(egui_output.shapes);
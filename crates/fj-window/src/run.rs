@@ -5,11 +5,11 @@
 
 use std::error;
 
-use fj_host::Watcher;
+use fj_host::{Event as ModelEvent, Parameters, Watcher};
 use fj_operations::shape_processor::ShapeProcessor;
 use fj_viewer::{
     camera::Camera,
-    graphics::{self, DrawConfig, Renderer},
+    graphics::{self, DrawConfig, ModelParameter, ModelParameters, Renderer},
     input,
     screen::{NormalizedPosition, Screen as _, Size},
 };
@@ -28,7 +28,7 @@ use crate::window::{self, Window};
 
 /// Initializes a model viewer for a given model and enters its process loop.
 pub fn run(
-    watcher: Watcher,
+    mut watcher: Watcher,
     shape_processor: ShapeProcessor,
 ) -> Result<(), Error> {
     let event_loop = EventLoop::new();
@@ -42,6 +42,7 @@ pub fn run(
     let mut renderer = block_on(Renderer::new(&window))?;
 
     let mut draw_config = DrawConfig::default();
+    let mut model_parameters = ModelParameters::default();
 
     let mut shape = None;
     let mut camera = None;
@@ -49,37 +50,98 @@ pub fn run(
     event_loop.run(move |event, _, control_flow| {
         trace!("Handling event: {:?}", event);
 
-        if let Some(new_shape) = watcher.receive() {
-            match shape_processor.process(&new_shape) {
-                Ok(new_shape) => {
-                    renderer.update_geometry(
-                        (&new_shape.mesh).into(),
-                        (&new_shape.debug_info).into(),
-                        new_shape.aabb,
-                    );
-
-                    if camera.is_none() {
-                        camera = Some(Camera::new(&new_shape.aabb));
-                    }
-
-                    shape = Some(new_shape);
-                }
-                Err(err) => {
-                    // Can be cleaned up, once `Report` is stable:
-                    // https://doc.rust-lang.org/std/error/struct.Report.html
+        match watcher.receive() {
+            Some(ModelEvent::BuildStarted) => {
+                renderer.update_status("Building model...");
+            }
+            Some(ModelEvent::BuildCancelled) => {
+                renderer.update_status("Model changed, restarting build...");
+            }
+            Some(ModelEvent::MetadataReady(metadata)) => {
+                let parameters = metadata
+                    .parameters
+                    .into_iter()
+                    .map(|parameter| ModelParameter {
+                        value: watcher
+                            .parameters()
+                            .get(&parameter.name)
+                            .cloned()
+                            .or(parameter.default)
+                            .unwrap_or_default(),
+                        name: parameter.name,
+                        ty: parameter.ty,
+                        min: parameter.min,
+                        max: parameter.max,
+                        description: parameter.description,
+                    })
+                    .collect();
 
-                    println!("Shape processing error: {}", err);
+                model_parameters.update(parameters);
+            }
+            Some(ModelEvent::EvaluationStarted) => {
+                renderer.update_status("Evaluating model...");
+                renderer.update_errors(Vec::new());
+            }
+            Some(ModelEvent::ShapeReady(new_shape)) => {
+                match shape_processor.process(&new_shape) {
+                    Ok(new_shape) => {
+                        renderer.update_geometry(
+                            (&new_shape.mesh).into(),
+                            (&new_shape.debug_info).into(),
+                            new_shape.aabb,
+                        );
 
-                    let mut current_err = &err as &dyn error::Error;
-                    while let Some(err) = current_err.source() {
-                        println!();
-                        println!("Caused by:");
-                        println!("    {}", err);
+                        if camera.is_none() {
+                            camera = Some(Camera::new(&new_shape.aabb));
+                        }
 
-                        current_err = err;
+                        shape = Some(new_shape);
+                        renderer.update_status("");
+                    }
+                    Err(err) => {
+                        print_error("Shape processing error", &err);
+                        renderer.update_status("Error processing shape");
                     }
                 }
             }
+            Some(ModelEvent::CompileFailed(diagnostics)) => {
+                println!("Error compiling model");
+                for diagnostic in &diagnostics {
+                    println!("    {}", diagnostic);
+                }
+
+                let errors = diagnostics
+                    .iter()
+                    .map(|diagnostic| {
+                        if diagnostic.rendered.is_empty() {
+                            diagnostic.to_string()
+                        } else {
+                            diagnostic.rendered.clone()
+                        }
+                    })
+                    .collect();
+
+                renderer.update_status("Error compiling model");
+                renderer.update_errors(errors);
+            }
+            Some(ModelEvent::LoadFailed(fj_host::Error::Panic(message))) => {
+                // Models panic on invalid parameters, for example. The message
+                // is all the user needs to fix that.
+                println!("Model panicked: {}", message);
+                renderer.update_status("Model panicked");
+                renderer.update_errors(vec![message]);
+            }
+            Some(ModelEvent::LoadFailed(err)) => {
+                print_error("Error loading model", &err);
+                renderer.update_status("Error loading model");
+                renderer.update_errors(vec![report(&err)]);
+            }
+            Some(ModelEvent::WatchFailed(err)) => {
+                print_error("Error watching model", &err);
+                renderer.update_status("Error watching model");
+                renderer.update_errors(vec![report(&err)]);
+            }
+            None => {}
         }
 
         //
@@ -167,14 +229,35 @@ pub fn run(
                 window.window().request_redraw();
             }
             Event::RedrawRequested(_) => {
-                if let (Some(shape), Some(camera)) = (&shape, &mut camera) {
-                    camera.update_planes(&shape.aabb);
+                // The UI is drawn, even if there's no shape yet. Otherwise the
+                // status and any errors from the first load wouldn't be shown.
+                let camera = match (&shape, &mut camera) {
+                    (Some(shape), Some(camera)) => {
+                        camera.update_planes(&shape.aabb);
+                        Some(&*camera)
+                    }
+                    _ => None,
+                };
 
-                    if let Err(err) =
-                        renderer.draw(camera, &mut draw_config, window.window())
-                    {
-                        warn!("Draw error: {}", err);
+                if let Err(err) = renderer.draw(
+                    camera,
+                    &mut draw_config,
+                    &mut model_parameters,
+                    window.window(),
+                ) {
+                    warn!("Draw error: {}", err);
+                }
+
+                if model_parameters.take_changed() {
+                    let mut parameters = Parameters::empty();
+                    for (name, value) in model_parameters.values() {
+                        // The model uses the default for missing parameters.
+                        if !value.is_empty() {
+                            parameters.insert(name, value);
+                        }
                     }
+
+                    watcher.update_parameters(parameters);
                 }
             }
             _ => {}
@@ -264,6 +347,26 @@ fn input_event(
     }
 }
 
+fn print_error(context: &str, err: &dyn error::Error) {
+    println!("{}: {}", context, report(err));
+}
+
+/// Describe an error, including the errors that caused it
+fn report(err: &dyn error::Error) -> String {
+    // Can be cleaned up, once `Report` is stable:
+    // https://doc.rust-lang.org/std/error/struct.Report.html
+
+    let mut report = err.to_string();
+
+    let mut current_err = err;
+    while let Some(err) = current_err.source() {
+        report = format!("{}\n\nCaused by:\n    {}", report, err);
+        current_err = err;
+    }
+
+    report
+}
+
 /// Returns true/false if focus point point should be created/removed
 /// None means no change to focus point is needed
 fn focus_event(event: &Event<()>) -> Option<bool> {
@@ -0,0 +1,25 @@
+use std::{env, process::Command};
+
+fn main() {
+    println!("cargo:rustc-env=FJ_RUSTC_VERSION={}", rustc_version());
+    println!("cargo:rerun-if-changed=build.rs");
+}
+
+/// Get the version of the compiler that compiles this crate
+///
+/// Types from the standard library are passed between a model and the host
+/// application. Their layout is only guaranteed to match, if both have been
+/// compiled by the same compiler.
+fn rustc_version() -> String {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+
+    let output = Command::new(&rustc)
+        .arg("--version")
+        .output()
+        .unwrap_or_else(|err| panic!("Failed to run `{rustc}`: {err}"));
+    if !output.status.success() {
+        panic!("`{rustc} --version` failed: {}", output.status);
+    }
+
+    String::from_utf8_lossy(&output.stdout).trim().to_owned()
+}
@@ -0,0 +1,218 @@
+//! FFI-safe versions of common standard library types
+//!
+//! The layout of types like `Vec` and `String` is not specified, so they can't
+//! be passed between a model and the host application directly. The types in
+//! this module store their raw parts instead.
+
+use std::{fmt, mem, ops::Deref, str};
+
+#[cfg(feature = "serde")]
+use serde::{de, ser, Deserialize};
+
+/// An FFI-safe [`Vec`]
+#[repr(C)]
+pub struct FfiVec<T> {
+    // The fields are the raw parts of a `Vec`. This can be cleaned up, once
+    // `Vec::into_raw_parts` is stable.
+    ptr: *mut T,
+    length: usize,
+    capacity: usize,
+}
+
+impl<T> From<Vec<T>> for FfiVec<T> {
+    fn from(mut vec: Vec<T>) -> Self {
+        let ptr = vec.as_mut_ptr();
+        let length = vec.len();
+        let capacity = vec.capacity();
+
+        // We're taking ownership of the memory here, so we can't allow `vec`
+        // to deallocate it.
+        mem::forget(vec);
+
+        Self {
+            ptr,
+            length,
+            capacity,
+        }
+    }
+}
+
+impl<T> From<FfiVec<T>> for Vec<T> {
+    fn from(vec: FfiVec<T>) -> Self {
+        // The memory is owned by the returned `Vec` now. `vec` must not
+        // deallocate it.
+        let vec = mem::ManuallyDrop::new(vec);
+
+        // This is sound, as the raw parts come from an original `Vec` and
+        // haven't been modified since.
+        unsafe { Vec::from_raw_parts(vec.ptr, vec.length, vec.capacity) }
+    }
+}
+
+impl<T> Deref for FfiVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.length) }
+    }
+}
+
+impl<T: Clone> Clone for FfiVec<T> {
+    fn clone(&self) -> Self {
+        self.to_vec().into()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for FfiVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for FfiVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for FfiVec<T> {}
+
+impl<T> Drop for FfiVec<T> {
+    fn drop(&mut self) {
+        // This is sound, as the raw parts come from an original `Vec` and
+        // haven't been modified since.
+        unsafe {
+            drop(Vec::from_raw_parts(self.ptr, self.length, self.capacity));
+        }
+    }
+}
+
+// `FfiVec` can be `Send` and `Sync` under the same conditions as `Vec`, as it
+// owns its buffer in the same way.
+unsafe impl<T: Send> Send for FfiVec<T> {}
+unsafe impl<T: Sync> Sync for FfiVec<T> {}
+
+/// An FFI-safe [`String`]
+#[derive(Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct FfiString(FfiVec<u8>);
+
+impl FfiString {
+    /// Access the string
+    pub fn as_str(&self) -> &str {
+        // This is sound, as the bytes can only have come from a `String`.
+        unsafe { str::from_utf8_unchecked(&self.0) }
+    }
+}
+
+impl From<String> for FfiString {
+    fn from(string: String) -> Self {
+        Self(string.into_bytes().into())
+    }
+}
+
+impl From<&str> for FfiString {
+    fn from(string: &str) -> Self {
+        String::from(string).into()
+    }
+}
+
+impl From<FfiString> for String {
+    fn from(string: FfiString) -> Self {
+        // This is sound, as the bytes can only have come from a `String`.
+        unsafe { String::from_utf8_unchecked(string.0.into()) }
+    }
+}
+
+impl Deref for FfiString {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for FfiString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for FfiString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ser::Serialize for FfiString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> de::Deserialize<'de> for FfiString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// An FFI-safe [`Option`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum FfiOption<T> {
+    /// No value
+    None,
+
+    /// Some value
+    Some(T),
+}
+
+impl<T> From<Option<T>> for FfiOption<T> {
+    fn from(option: Option<T>) -> Self {
+        match option {
+            Some(value) => Self::Some(value),
+            None => Self::None,
+        }
+    }
+}
+
+impl<T> From<FfiOption<T>> for Option<T> {
+    fn from(option: FfiOption<T>) -> Self {
+        match option {
+            FfiOption::Some(value) => Some(value),
+            FfiOption::None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FfiString, FfiVec};
+
+    #[test]
+    fn vec_roundtrip() {
+        let vec = vec![[1., 2.], [3., 4.]];
+
+        let ffi_vec = FfiVec::from(vec.clone());
+        assert_eq!(&*ffi_vec, vec.as_slice());
+        assert_eq!(ffi_vec.clone(), ffi_vec);
+        assert_eq!(Vec::from(ffi_vec), vec);
+    }
+
+    #[test]
+    fn string_roundtrip() {
+        let string = FfiString::from("model.stl");
+
+        assert_eq!(string.as_str(), "model.stl");
+        assert_eq!(String::from(string.clone()), "model.stl");
+        assert_eq!(format!("{string:?}"), r#""model.stl""#);
+    }
+}
@@ -0,0 +1,110 @@
+//! FFI-safe versions of the model metadata
+
+use super::{FfiOption, FfiString, FfiVec};
+
+/// The FFI-safe version of [`crate::ModelMetadata`]
+///
+/// Returned by the `model_metadata` function of a model library.
+#[derive(Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct ModelMetadata {
+    name: FfiString,
+    description: FfiOption<FfiString>,
+    parameters: FfiVec<ParameterMetadata>,
+}
+
+impl From<crate::ModelMetadata> for ModelMetadata {
+    fn from(metadata: crate::ModelMetadata) -> Self {
+        Self {
+            name: metadata.name.into(),
+            description: ffi_string(metadata.description),
+            parameters: metadata
+                .parameters
+                .into_iter()
+                .map(ParameterMetadata::from)
+                .collect::<Vec<_>>()
+                .into(),
+        }
+    }
+}
+
+impl From<ModelMetadata> for crate::ModelMetadata {
+    fn from(metadata: ModelMetadata) -> Self {
+        Self {
+            name: metadata.name.into(),
+            description: string(metadata.description),
+            parameters: Vec::from(metadata.parameters)
+                .into_iter()
+                .map(crate::ParameterMetadata::from)
+                .collect(),
+        }
+    }
+}
+
+/// The FFI-safe version of [`crate::ParameterMetadata`]
+#[derive(Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct ParameterMetadata {
+    name: FfiString,
+    ty: FfiString,
+    default: FfiOption<FfiString>,
+    min: FfiOption<FfiString>,
+    max: FfiOption<FfiString>,
+    description: FfiOption<FfiString>,
+}
+
+impl From<crate::ParameterMetadata> for ParameterMetadata {
+    fn from(parameter: crate::ParameterMetadata) -> Self {
+        Self {
+            name: parameter.name.into(),
+            ty: parameter.ty.into(),
+            default: ffi_string(parameter.default),
+            min: ffi_string(parameter.min),
+            max: ffi_string(parameter.max),
+            description: ffi_string(parameter.description),
+        }
+    }
+}
+
+impl From<ParameterMetadata> for crate::ParameterMetadata {
+    fn from(parameter: ParameterMetadata) -> Self {
+        Self {
+            name: parameter.name.into(),
+            ty: parameter.ty.into(),
+            default: string(parameter.default),
+            min: string(parameter.min),
+            max: string(parameter.max),
+            description: string(parameter.description),
+        }
+    }
+}
+
+fn ffi_string(string: Option<String>) -> FfiOption<FfiString> {
+    string.map(FfiString::from).into()
+}
+
+fn string(string: FfiOption<FfiString>) -> Option<String> {
+    Option::from(string).map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn metadata_roundtrip() {
+        let metadata = crate::ModelMetadata {
+            name: String::from("cuboid"),
+            description: None,
+            parameters: vec![crate::ParameterMetadata {
+                name: String::from("x"),
+                ty: String::from("f64"),
+                default: Some(String::from("3.0")),
+                min: Some(String::from("0.1")),
+                max: None,
+                description: Some(String::from("The length")),
+            }],
+        };
+
+        let ffi_metadata = super::ModelMetadata::from(metadata.clone());
+        assert_eq!(crate::ModelMetadata::from(ffi_metadata), metadata);
+    }
+}
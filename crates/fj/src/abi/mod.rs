@@ -0,0 +1,137 @@
+//! Types that are shared between a model and the host application
+//!
+//! The items in this module are used by the code that the [`model`] macro
+//! generates, and by the host application that loads the model. They are not
+//! meant to be used directly by models.
+//!
+//! [`model`]: crate::model
+
+mod ffi_safe;
+mod metadata;
+
+pub use self::{
+    ffi_safe::{FfiOption, FfiString, FfiVec},
+    metadata::{ModelMetadata, ParameterMetadata},
+};
+
+use std::{any::Any, panic, slice, str};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Shape;
+
+/// The value returned by the `model` function of a model library
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C)]
+pub enum ModelResult {
+    /// The model returned a shape
+    Shape(Shape),
+
+    /// The model panicked
+    ///
+    /// Contains the panic message, if it was a string.
+    Panic(FfiString),
+}
+
+/// The version of `fj` that a model was compiled against
+///
+/// Includes the version of the compiler, as the layout of the types that are
+/// passed between model and host depends on it.
+///
+/// Exported by model libraries through the `fj_version` function, which the
+/// host calls before the `model` function. The layout of this struct must
+/// never change, so hosts can check the version of any model library.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Version {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl Version {
+    /// The version of `fj` that is being compiled, and of its compiler
+    pub const fn current() -> Self {
+        const VERSION: &str = concat!(
+            env!("CARGO_PKG_VERSION"),
+            " (",
+            env!("FJ_RUSTC_VERSION"),
+            ")"
+        );
+
+        Self {
+            ptr: VERSION.as_ptr(),
+            len: VERSION.len(),
+        }
+    }
+
+    /// Access the version as a string
+    ///
+    /// # Safety
+    ///
+    /// The `Version` must have been returned by the `fj_version` function of a
+    /// model library, and that library must still be loaded.
+    pub unsafe fn as_str(&self) -> &str {
+        let bytes = slice::from_raw_parts(self.ptr, self.len);
+        str::from_utf8(bytes).unwrap_or("(invalid version)")
+    }
+}
+
+/// Call a model function, catching any panic
+///
+/// Unwinding across the `extern "C"` boundary of the `model` function is
+/// undefined behavior. The panic is returned as a value instead.
+pub fn catch_panic(f: impl FnOnce() -> Shape) -> ModelResult {
+    // The closure is not necessarily unwind-safe, but it is never called again
+    // after a panic, so there is no broken state that could be observed.
+    match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+        Ok(shape) => ModelResult::Shape(shape),
+        Err(payload) => ModelResult::Panic(panic_message(payload).into()),
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return (*message).to_owned();
+    }
+
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(_) => String::from("Model panicked"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Circle, Shape, Sketch};
+
+    use super::{catch_panic, FfiString, ModelResult, Version};
+
+    #[test]
+    fn catch_panic_returns_shape_or_message() {
+        let shape: Shape = Sketch::from_circle(Circle::from_radius(1.)).into();
+
+        assert_eq!(
+            catch_panic(|| shape.clone()),
+            ModelResult::Shape(shape.clone())
+        );
+        assert_eq!(
+            catch_panic(|| panic!("Invalid radius: {}", -1)),
+            ModelResult::Panic(FfiString::from("Invalid radius: -1"))
+        );
+    }
+
+    #[test]
+    fn current_version() {
+        let version = Version::current();
+        assert_eq!(
+            unsafe { version.as_str() },
+            format!(
+                "{} ({})",
+                env!("CARGO_PKG_VERSION"),
+                env!("FJ_RUSTC_VERSION")
+            )
+        );
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{abi::FfiString, Shape};
+
+/// A triangle mesh, imported from a file
+///
+/// Supported are STL files, in both the ASCII and the binary encoding, and 3MF
+/// files. The format is determined by the file extension.
+///
+/// Colors are taken from the file, where available. That is the case for 3MF
+/// files that use color groups, and for binary STL files that store colors in
+/// the attribute bytes of each triangle. All other triangles get the color of
+/// the import.
+///
+/// Relative paths are resolved relative to the working directory of the
+/// Fornjot application. To refer to a file that is part of the model, consider
+/// using `concat!(env!("CARGO_MANIFEST_DIR"), "/path/to/file.stl")`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C)]
+pub struct Import {
+    // `Shape` needs to be FFI-safe, meaning it can't store a `String` directly.
+    path: FfiString,
+
+    // The color of the triangles that don't have a color of their own, in RGBA
+    color: [u8; 4],
+}
+
+impl Import {
+    /// Create an `Import` of the file at the given path
+    pub fn from_path(path: impl Into<String>) -> Self {
+        let path: String = path.into();
+
+        Self {
+            path: path.into(),
+            color: [255, 0, 0, 255],
+        }
+    }
+
+    /// Set the rendering color of the triangles without a color of their own
+    pub fn with_color(mut self, color: [u8; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Access the path of the imported file
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    /// Get the rendering color of the triangles without a color of their own
+    pub fn color(&self) -> [u8; 4] {
+        self.color
+    }
+}
+
+impl From<Import> for Shape {
+    fn from(shape: Import) -> Self {
+        Self::Import(shape)
+    }
+}
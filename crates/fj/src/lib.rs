@@ -18,16 +18,25 @@
 
 #![warn(missing_docs)]
 
+pub mod abi;
 pub mod syntax;
 
 mod angle;
 mod group;
+mod import;
+mod metadata;
 mod shape_2d;
 mod sweep;
 mod transform;
 
 pub use self::{
-    angle::*, group::Group, shape_2d::*, sweep::Sweep, transform::Transform,
+    angle::*,
+    group::Group,
+    import::Import,
+    metadata::{ModelMetadata, ParameterMetadata},
+    shape_2d::*,
+    sweep::Sweep,
+    transform::Transform,
 };
 pub use fj_proc::*;
 #[cfg(feature = "serde")]
@@ -41,6 +50,9 @@ pub enum Shape {
     /// A group of two 3-dimensional shapes
     Group(Box<Group>),
 
+    /// A triangle mesh, imported from a file
+    Import(Import),
+
     /// A 2D shape
     Shape2d(Shape2d),
 
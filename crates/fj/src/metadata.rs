@@ -0,0 +1,50 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Information about a model, as defined using the [`model`] macro
+///
+/// Model libraries export this information, so the host application can
+/// present the model's parameters to the user. It is passed to the host as
+/// [`abi::ModelMetadata`].
+///
+/// [`model`]: crate::model
+/// [`abi::ModelMetadata`]: crate::abi::ModelMetadata
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModelMetadata {
+    /// The name of the model function
+    pub name: String,
+
+    /// The doc comment of the model function, if any
+    pub description: Option<String>,
+
+    /// The parameters of the model, in the order of the function's arguments
+    pub parameters: Vec<ParameterMetadata>,
+}
+
+/// Information about a parameter of a model
+///
+/// Defaults and limits are given as source code, as written in the `#[param]`
+/// attribute. Limits can refer to other parameters, so they can't always be
+/// evaluated in advance.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParameterMetadata {
+    /// The name of the parameter
+    pub name: String,
+
+    /// The Rust type of the parameter (e.g. `f64`)
+    pub ty: String,
+
+    /// The default value, if any
+    pub default: Option<String>,
+
+    /// The minimum value, if any
+    pub min: Option<String>,
+
+    /// The maximum value, if any
+    pub max: Option<String>,
+
+    /// The doc comment of the parameter, if any
+    pub description: Option<String>,
+}
@@ -266,6 +266,10 @@ impl Drop for PolyChain {
 // contains, making sure memory ownership rules are observed.
 unsafe impl Send for PolyChain {}
 
+// `PolyChain` can be `Sync`, because the buffer behind `ptr` is never modified
+// through a shared reference, and the reference counter is atomic.
+unsafe impl Sync for PolyChain {}
+
 #[cfg(feature = "serde")]
 impl ser::Serialize for PolyChain {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
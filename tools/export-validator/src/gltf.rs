@@ -0,0 +1,311 @@
+//! Reading the triangles back from glTF files
+//!
+//! Node transforms are applied, and the result is converted from the
+//! conventions of glTF (y-up, meters) back to Fornjot's (z-up, millimeters).
+
+use anyhow::{bail, Context as _};
+use serde_json::Value;
+
+use crate::stats::Triangle;
+
+/// A transform, as a row-major 4x4 matrix
+type Matrix = [[f64; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1., 0., 0., 0.],
+    [0., 1., 0., 0.],
+    [0., 0., 1., 0.],
+    [0., 0., 0., 1.],
+];
+
+pub fn read(data: &[u8], binary: bool) -> anyhow::Result<Vec<Triangle>> {
+    let (document, buffer) = if binary {
+        split_glb(data)?
+    } else {
+        let document: Value = serde_json::from_slice(data)?;
+        let buffer = embedded_buffer(&document)?;
+        (document, buffer)
+    };
+
+    let scene = document["scene"].as_u64().unwrap_or(0) as usize;
+    let roots = document["scenes"][scene]["nodes"]
+        .as_array()
+        .context("Missing scene")?;
+
+    let mut triangles = Vec::new();
+    for root in roots {
+        let root = index(root)?;
+        add_node(&document, &buffer, root, &IDENTITY, &mut triangles)?;
+    }
+
+    let triangles = triangles
+        .into_iter()
+        .map(|triangle| {
+            triangle.map(|[x, y, z]| [x, -z, y].map(|value| value * 1000.))
+        })
+        .collect();
+
+    Ok(triangles)
+}
+
+fn split_glb(data: &[u8]) -> anyhow::Result<(Value, Vec<u8>)> {
+    const JSON: u32 = 0x4E4F534A;
+    const BIN: u32 = 0x004E4942;
+
+    if !data.starts_with(b"glTF") {
+        bail!("Missing GLB magic");
+    }
+
+    let mut document = None;
+    let mut buffer = Vec::new();
+
+    let mut rest = data.get(12..).context("Incomplete GLB header")?;
+    while !rest.is_empty() {
+        let header = rest.get(..8).context("Incomplete chunk header")?;
+        let length = u32::from_le_bytes(header[..4].try_into()?) as usize;
+        let kind = u32::from_le_bytes(header[4..].try_into()?);
+
+        let chunk = rest.get(8..8 + length).context("Incomplete chunk")?;
+        match kind {
+            JSON => document = Some(serde_json::from_slice(chunk)?),
+            BIN => buffer = chunk.to_vec(),
+            _ => {}
+        }
+
+        rest = &rest[8 + length..];
+    }
+
+    Ok((document.context("Missing JSON chunk")?, buffer))
+}
+
+fn embedded_buffer(document: &Value) -> anyhow::Result<Vec<u8>> {
+    let uri = match document["buffers"][0]["uri"].as_str() {
+        Some(uri) => uri,
+        None => return Ok(Vec::new()),
+    };
+
+    let (_, data) = uri
+        .split_once(";base64,")
+        .context("Only embedded base64 buffers are supported")?;
+
+    Ok(base64::decode(data)?)
+}
+
+fn add_node(
+    document: &Value,
+    buffer: &[u8],
+    index: usize,
+    parent: &Matrix,
+    triangles: &mut Vec<Triangle>,
+) -> anyhow::Result<()> {
+    let node = &document["nodes"][index];
+    let transform = multiply(parent, &local_transform(node)?);
+
+    if let Some(mesh) = node["mesh"].as_u64() {
+        let primitives = document["meshes"][mesh as usize]["primitives"]
+            .as_array()
+            .context("Missing primitives")?;
+
+        for primitive in primitives {
+            if primitive["mode"].as_u64().unwrap_or(4) != 4 {
+                bail!("Only triangle primitives are supported");
+            }
+
+            let positions = read_accessor(
+                document,
+                buffer,
+                self::index(&primitive["attributes"]["POSITION"])?,
+            )?;
+            let indices: Vec<usize> = match primitive["indices"].as_u64() {
+                Some(indices) => {
+                    read_accessor(document, buffer, indices as usize)?
+                        .into_iter()
+                        .map(|index| index[0] as usize)
+                        .collect()
+                }
+                None => (0..positions.len()).collect(),
+            };
+
+            for triangle in indices.chunks_exact(3) {
+                let mut points = [[0.; 3]; 3];
+                for (point, &index) in points.iter_mut().zip(triangle) {
+                    let position =
+                        positions.get(index).context("Invalid index")?;
+                    *point = apply(&transform, position)?;
+                }
+                triangles.push(points);
+            }
+        }
+    }
+
+    if let Some(children) = node["children"].as_array() {
+        for child in children {
+            add_node(
+                document,
+                buffer,
+                self::index(child)?,
+                &transform,
+                triangles,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn local_transform(node: &Value) -> anyhow::Result<Matrix> {
+    if let Some(matrix) = node["matrix"].as_array() {
+        let values = numbers(matrix)?;
+        if values.len() != 16 {
+            bail!("Invalid matrix");
+        }
+
+        // glTF matrices are stored in column-major order.
+        let mut transform = IDENTITY;
+        for (row, row_values) in transform.iter_mut().enumerate() {
+            for (column, value) in row_values.iter_mut().enumerate() {
+                *value = values[column * 4 + row];
+            }
+        }
+
+        return Ok(transform);
+    }
+
+    let vector = |name: &str, default: &[f64]| -> anyhow::Result<Vec<f64>> {
+        match node[name].as_array() {
+            Some(values) => numbers(values),
+            None => Ok(default.to_vec()),
+        }
+    };
+    let translation = vector("translation", &[0., 0., 0.])?;
+    let [x, y, z, w]: [f64; 4] = vector("rotation", &[0., 0., 0., 1.])?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Invalid rotation"))?;
+    let scale = vector("scale", &[1., 1., 1.])?;
+    if translation.len() != 3 || scale.len() != 3 {
+        bail!("Invalid translation or scale");
+    }
+
+    let rotation = [
+        [
+            1. - 2. * (y * y + z * z),
+            2. * (x * y - z * w),
+            2. * (x * z + y * w),
+        ],
+        [
+            2. * (x * y + z * w),
+            1. - 2. * (x * x + z * z),
+            2. * (y * z - x * w),
+        ],
+        [
+            2. * (x * z - y * w),
+            2. * (y * z + x * w),
+            1. - 2. * (x * x + y * y),
+        ],
+    ];
+
+    let mut transform = IDENTITY;
+    for (row, rotation_row) in rotation.iter().enumerate() {
+        for (column, value) in rotation_row.iter().enumerate() {
+            transform[row][column] = value * scale[column];
+        }
+        transform[row][3] = translation[row];
+    }
+
+    Ok(transform)
+}
+
+fn read_accessor(
+    document: &Value,
+    buffer: &[u8],
+    index: usize,
+) -> anyhow::Result<Vec<Vec<f64>>> {
+    let accessor = &document["accessors"][index];
+    let view = &document["bufferViews"][self::index(&accessor["bufferView"])?];
+
+    let num_components = match accessor["type"].as_str() {
+        Some("SCALAR") => 1,
+        Some("VEC3") => 3,
+        kind => bail!("Unsupported accessor type {kind:?}"),
+    };
+    let component_size = match accessor["componentType"].as_u64() {
+        Some(5121) => 1,
+        Some(5123) => 2,
+        Some(5125) | Some(5126) => 4,
+        kind => bail!("Unsupported component type {kind:?}"),
+    };
+    let component_type = accessor["componentType"].as_u64();
+
+    let offset = view["byteOffset"].as_u64().unwrap_or(0) as usize
+        + accessor["byteOffset"].as_u64().unwrap_or(0) as usize;
+    let stride = view["byteStride"]
+        .as_u64()
+        .map(|stride| stride as usize)
+        .unwrap_or(num_components * component_size);
+    let count = self::index(&accessor["count"])?;
+
+    (0..count)
+        .map(|i| -> anyhow::Result<Vec<f64>> {
+            (0..num_components)
+                .map(|component| -> anyhow::Result<f64> {
+                    let start =
+                        offset + i * stride + component * component_size;
+                    let bytes = buffer
+                        .get(start..start + component_size)
+                        .context("Accessor out of bounds")?;
+
+                    let value = match component_type {
+                        Some(5121) => f64::from(bytes[0]),
+                        Some(5123) => {
+                            f64::from(u16::from_le_bytes(bytes.try_into()?))
+                        }
+                        Some(5125) => {
+                            f64::from(u32::from_le_bytes(bytes.try_into()?))
+                        }
+                        _ => f64::from(f32::from_le_bytes(bytes.try_into()?)),
+                    };
+
+                    Ok(value)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn apply(transform: &Matrix, point: &[f64]) -> anyhow::Result<[f64; 3]> {
+    if point.len() != 3 {
+        bail!("Invalid position");
+    }
+
+    let mut result = [0.; 3];
+    for (value, row) in result.iter_mut().zip(transform) {
+        *value =
+            row[0] * point[0] + row[1] * point[1] + row[2] * point[2] + row[3];
+    }
+
+    Ok(result)
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut result = [[0.; 4]; 4];
+
+    for (row, result_row) in result.iter_mut().enumerate() {
+        for (column, value) in result_row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[row][k] * b[k][column]).sum();
+        }
+    }
+
+    result
+}
+
+fn numbers(values: &[Value]) -> anyhow::Result<Vec<f64>> {
+    values
+        .iter()
+        .map(|value| value.as_f64().context("Expected number"))
+        .collect()
+}
+
+fn index(value: &Value) -> anyhow::Result<usize> {
+    let index = value.as_u64().context("Expected index")?;
+    Ok(index.try_into()?)
+}
@@ -0,0 +1,279 @@
+//! Reading the triangles back from exported files
+//!
+//! STL and 3MF files are read using Fornjot's own import. The other formats
+//! are read by the minimal readers in this module, which only support what
+//! the exporter writes.
+
+use std::{fs, path::Path, str};
+
+use anyhow::{anyhow, bail, Context as _};
+use fj_export::Format;
+use fj_operations::shape_processor::ShapeProcessor;
+
+use crate::{gltf, stats::Triangle};
+
+pub fn read(
+    path: &Path,
+    format: Format,
+    processor: &ShapeProcessor,
+) -> anyhow::Result<Vec<Triangle>> {
+    match format {
+        Format::Stl(_) | Format::ThreeMf => read_with_fornjot(path, processor),
+        Format::Obj => read_obj(&fs::read_to_string(path)?),
+        Format::Ply(_) => read_ply(&fs::read(path)?),
+        Format::Gltf { binary } => gltf::read(&fs::read(path)?, binary),
+        Format::Step | Format::Svg | Format::Dxf => {
+            bail!("Can't read triangles from {format:?} file")
+        }
+    }
+}
+
+fn read_with_fornjot(
+    path: &Path,
+    processor: &ShapeProcessor,
+) -> anyhow::Result<Vec<Triangle>> {
+    let path = path.to_str().context("Path is not valid UTF-8")?;
+    let shape = fj::Shape::from(fj::Import::from_path(path));
+
+    let processed = processor.process(&shape)?;
+    let triangles = processed
+        .mesh
+        .triangles()
+        .map(|triangle| {
+            triangle
+                .inner
+                .points()
+                .map(|point| point.coords.components.map(|s| s.into_f64()))
+        })
+        .collect();
+
+    Ok(triangles)
+}
+
+fn read_obj(obj: &str) -> anyhow::Result<Vec<Triangle>> {
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+
+    for line in obj.lines() {
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => {
+                let mut vertex = [0.; 3];
+                for value in &mut vertex {
+                    *value = tokens
+                        .next()
+                        .context("Missing vertex coordinate")?
+                        .parse()?;
+                }
+                vertices.push(vertex);
+            }
+            Some("f") => {
+                let face = tokens
+                    .map(|token| -> anyhow::Result<[f64; 3]> {
+                        // Faces might also refer to texture coordinates and
+                        // normals, separated by `/`.
+                        let index = token.split('/').next().unwrap_or(token);
+                        let index: usize = index.parse()?;
+
+                        // OBJ indices start at 1.
+                        let vertex = index
+                            .checked_sub(1)
+                            .and_then(|index| vertices.get(index))
+                            .ok_or_else(|| anyhow!("Invalid index {index}"))?;
+
+                        Ok(*vertex)
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                triangles.extend(fan(&face)?);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+fn read_ply(ply: &[u8]) -> anyhow::Result<Vec<Triangle>> {
+    const END_HEADER: &[u8] = b"end_header\n";
+
+    let header_len = ply
+        .windows(END_HEADER.len())
+        .position(|window| window == END_HEADER)
+        .context("Missing end of header")?
+        + END_HEADER.len();
+    let header = str::from_utf8(&ply[..header_len])?;
+
+    let mut binary = false;
+    let mut elements: Vec<(String, usize, Vec<Property>)> = Vec::new();
+
+    for line in header.lines() {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+        match tokens[..] {
+            ["format", "ascii", _] => binary = false,
+            ["format", "binary_little_endian", _] => binary = true,
+            ["format", format, _] => bail!("Unsupported format `{format}`"),
+            ["element", name, count] => {
+                elements.push((name.to_owned(), count.parse()?, Vec::new()));
+            }
+            ["property", "list", count_type, item_type, name] => {
+                let (_, _, properties) =
+                    elements.last_mut().context("Property without element")?;
+                properties.push(Property {
+                    name: name.to_owned(),
+                    kind: PropertyKind::List(
+                        count_type.into(),
+                        item_type.into(),
+                    ),
+                });
+            }
+            ["property", scalar_type, name] => {
+                let (_, _, properties) =
+                    elements.last_mut().context("Property without element")?;
+                properties.push(Property {
+                    name: name.to_owned(),
+                    kind: PropertyKind::Scalar(scalar_type.into()),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let mut values = if binary {
+        Values::Binary(&ply[header_len..])
+    } else {
+        Values::Ascii(str::from_utf8(&ply[header_len..])?.split_whitespace())
+    };
+
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+
+    for (name, count, properties) in &elements {
+        for _ in 0..*count {
+            let mut vertex = [0.; 3];
+            let mut face = Vec::new();
+
+            for property in properties {
+                match &property.kind {
+                    PropertyKind::Scalar(kind) => {
+                        let value = values.next(kind)?;
+                        match property.name.as_str() {
+                            "x" => vertex[0] = value,
+                            "y" => vertex[1] = value,
+                            "z" => vertex[2] = value,
+                            _ => {}
+                        }
+                    }
+                    PropertyKind::List(count_kind, item_kind) => {
+                        let count = values.next(count_kind)? as usize;
+                        for _ in 0..count {
+                            face.push(values.next(item_kind)? as usize);
+                        }
+                    }
+                }
+            }
+
+            match name.as_str() {
+                "vertex" => vertices.push(vertex),
+                "face" => {
+                    let face = face
+                        .into_iter()
+                        .map(|index| lookup(&vertices, index))
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                    triangles.extend(fan(&face)?);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(triangles)
+}
+
+fn lookup(vertices: &[[f64; 3]], index: usize) -> anyhow::Result<[f64; 3]> {
+    vertices
+        .get(index)
+        .copied()
+        .ok_or_else(|| anyhow!("Invalid index {index}"))
+}
+
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+enum PropertyKind {
+    Scalar(String),
+    List(String, String),
+}
+
+enum Values<'r> {
+    Ascii(str::SplitWhitespace<'r>),
+    Binary(&'r [u8]),
+}
+
+impl<'r> Values<'r> {
+    fn next(&mut self, kind: &str) -> anyhow::Result<f64> {
+        match self {
+            Self::Ascii(tokens) => {
+                Ok(tokens.next().context("Unexpected end of file")?.parse()?)
+            }
+            Self::Binary(bytes) => {
+                let size = match kind {
+                    "char" | "uchar" | "int8" | "uint8" => 1,
+                    "short" | "ushort" | "int16" | "uint16" => 2,
+                    "int" | "uint" | "float" | "int32" | "uint32"
+                    | "float32" => 4,
+                    "double" | "float64" => 8,
+                    kind => bail!("Unsupported property type `{kind}`"),
+                };
+                let remaining: &'r [u8] = *bytes;
+                if remaining.len() < size {
+                    bail!("Unexpected end of file");
+                }
+
+                let (value, rest) = remaining.split_at(size);
+                *bytes = rest;
+
+                let value = match kind {
+                    "char" | "int8" => f64::from(value[0] as i8),
+                    "uchar" | "uint8" => f64::from(value[0]),
+                    "short" | "int16" => {
+                        f64::from(i16::from_le_bytes(value.try_into()?))
+                    }
+                    "ushort" | "uint16" => {
+                        f64::from(u16::from_le_bytes(value.try_into()?))
+                    }
+                    "int" | "int32" => {
+                        f64::from(i32::from_le_bytes(value.try_into()?))
+                    }
+                    "uint" | "uint32" => {
+                        f64::from(u32::from_le_bytes(value.try_into()?))
+                    }
+                    "float" | "float32" => {
+                        f64::from(f32::from_le_bytes(value.try_into()?))
+                    }
+                    _ => f64::from_le_bytes(value.try_into()?),
+                };
+
+                Ok(value)
+            }
+        }
+    }
+}
+
+/// Split a convex polygon into triangles
+pub fn fan(polygon: &[[f64; 3]]) -> anyhow::Result<Vec<Triangle>> {
+    if polygon.len() < 3 {
+        bail!("Polygon with less than 3 vertices");
+    }
+
+    let triangles = polygon[1..]
+        .windows(2)
+        .map(|edge| [polygon[0], edge[0], edge[1]])
+        .collect();
+
+    Ok(triangles)
+}
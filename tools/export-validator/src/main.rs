@@ -1,46 +1,151 @@
 mod ffi;
+mod gltf;
+mod import;
+mod stats;
 
-use std::{ffi::CString, fs, process::Command, ptr};
+use std::{ffi::CString, fs, path::Path, ptr};
 
-use anyhow::{anyhow, bail, Context as _};
+use anyhow::{anyhow, bail};
+use fj_export::{ExportOptions, Format, PlyEncoding, StlEncoding};
+use fj_host::{Model, Parameters};
+use fj_interop::processed_shape::ProcessedShape;
+use fj_operations::{cache::Cache, shape_processor::ShapeProcessor};
 use tempfile::tempdir;
 
+use crate::stats::{MeshStats, Triangle};
+
+/// The formats that are exported, then read back and compared
+///
+/// Each format is exported to a file with a distinct name, as imported files
+/// are cached by their path.
+///
+/// Formats that export the boundary representation, like STEP, are not
+/// included, as reading them back would require a full B-rep importer.
+const FORMATS: &[(&str, Format)] = &[
+    ("model.3mf", Format::ThreeMf),
+    ("binary.stl", Format::Stl(StlEncoding::Binary)),
+    ("ascii.stl", Format::Stl(StlEncoding::Ascii)),
+    ("model.obj", Format::Obj),
+    ("binary.ply", Format::Ply(PlyEncoding::BinaryLittleEndian)),
+    ("ascii.ply", Format::Ply(PlyEncoding::Ascii)),
+    ("model.gltf", Format::Gltf { binary: false }),
+    ("model.glb", Format::Gltf { binary: true }),
+];
+
 fn main() -> anyhow::Result<()> {
+    let mut num_failures = 0;
+
     for model in fs::read_dir("models")? {
         let model = model?;
         let model = model.file_name().into_string().map_err(|err| {
             anyhow!("Failed to convert directory name to `String`: {:?}", err)
         })?;
 
+        let processor = ShapeProcessor {
+            tolerance: None,
+            relative_tolerance: None,
+            angular_tolerance: None,
+            record_triangle_edge_checks: false,
+            cache: Cache::new(),
+        };
+
+        let parts = match process(&model, &processor) {
+            Ok(parts) => parts,
+            Err(err) => {
+                println!("{model}: FAILED to process model: {err:#}");
+                num_failures += 1;
+                continue;
+            }
+        };
+
+        let expected = MeshStats::new(&triangles(&parts));
+        let parts = parts.into_iter().map(|part| part.mesh).collect::<Vec<_>>();
+
         let dir = tempdir()?;
-        let file_name = format!("{model}.3mf");
-        let export_file_path = dir.path().join(file_name);
-        let export_file_path_str = export_file_path.to_str().unwrap();
-
-        let exit_status = Command::new("cargo")
-            .arg("run")
-            .arg("--")
-            .args(["--model", &model])
-            .args(["--export", export_file_path_str])
-            .status()?;
-
-        if !exit_status.success() {
-            bail!(
-                "Exporting model `{model}` failed with error code:\
-                {exit_status}"
-            );
+
+        for &(file_name, format) in FORMATS {
+            let name = format!("{format:?}");
+            let path = dir.path().join(format!("{model}-{file_name}"));
+
+            let result = ExportOptions {
+                format: Some(format),
+                ..ExportOptions::default()
+            }
+            .export_parts(&parts, &path)
+            .map_err(anyhow::Error::from)
+            .and_then(|()| verify(&path, format, &processor, &expected));
+
+            match result {
+                Ok(problems) if problems.is_empty() => {
+                    println!("{model} ({name}): ok");
+                }
+                Ok(problems) => {
+                    println!("{model} ({name}): FAILED");
+                    for problem in problems {
+                        println!("    {problem}");
+                    }
+                    num_failures += 1;
+                }
+                Err(err) => {
+                    println!("{model} ({name}): FAILED: {err:#}");
+                    num_failures += 1;
+                }
+            }
         }
+    }
 
+    if num_failures > 0 {
+        bail!("{num_failures} exports failed validation");
+    }
+
+    Ok(())
+}
+
+fn process(
+    model: &str,
+    processor: &ShapeProcessor,
+) -> anyhow::Result<Vec<ProcessedShape>> {
+    let model = Model::from_path(Path::new("models").join(model))?;
+    let shape = model.load_once(&Parameters::empty())?;
+
+    Ok(processor.process_parts(&shape)?)
+}
+
+/// Verify an exported file
+///
+/// Returns the differences between the file and the expected mesh. Errors
+/// indicate that the file could not be read at all.
+fn verify(
+    path: &Path,
+    format: Format,
+    processor: &ShapeProcessor,
+    expected: &MeshStats,
+) -> anyhow::Result<Vec<String>> {
+    if format == Format::ThreeMf {
         // Presumably we're using the library in the way it's intended, so this
         // might be sound?
         unsafe {
-            validate_model(export_file_path_str).with_context(|| {
-                format!("Could not validate model `{model}`")
-            })?;
+            validate_model(path.to_str().unwrap())?;
         }
     }
 
-    Ok(())
+    let triangles = import::read(path, format, processor)?;
+    let actual = MeshStats::new(&triangles);
+
+    Ok(expected.compare(&actual))
+}
+
+fn triangles(parts: &[ProcessedShape]) -> Vec<Triangle> {
+    parts
+        .iter()
+        .flat_map(|part| part.mesh.triangles())
+        .map(|triangle| {
+            triangle
+                .inner
+                .points()
+                .map(|point| point.coords.components.map(|s| s.into_f64()))
+        })
+        .collect()
 }
 
 unsafe fn validate_model(file: &str) -> anyhow::Result<()> {
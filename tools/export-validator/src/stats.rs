@@ -0,0 +1,152 @@
+//! Properties of triangle meshes that must survive a round trip through an
+//! exported file
+
+use std::collections::HashMap;
+
+/// A triangle, as an array of points
+pub type Triangle = [[f64; 3]; 3];
+
+/// The properties of a mesh that are compared
+pub struct MeshStats {
+    pub num_triangles: usize,
+
+    /// The bounding box of all vertices, as `[min, max]`
+    ///
+    /// `None`, if the mesh has no triangles.
+    pub aabb: Option<[[f64; 3]; 2]>,
+
+    /// Whether every edge is shared by exactly two triangles, with opposite
+    /// orientation
+    pub watertight: bool,
+}
+
+impl MeshStats {
+    pub fn new(triangles: &[Triangle]) -> Self {
+        let mut aabb: Option<[[f64; 3]; 2]> = None;
+        for point in triangles.iter().flatten() {
+            let [min, max] = aabb.get_or_insert([*point, *point]);
+            for ((min, max), value) in min.iter_mut().zip(max).zip(point) {
+                *min = min.min(*value);
+                *max = max.max(*value);
+            }
+        }
+
+        Self {
+            num_triangles: triangles.len(),
+            aabb,
+            watertight: is_watertight(triangles),
+        }
+    }
+
+    /// Compare the stats of a re-imported mesh to the expected ones
+    ///
+    /// Returns a description of each difference. Exported files might store
+    /// coordinates with lower precision, so bounding boxes are compared with a
+    /// tolerance relative to their size.
+    pub fn compare(&self, actual: &Self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.num_triangles != actual.num_triangles {
+            problems.push(format!(
+                "expected {} triangles, found {}",
+                self.num_triangles, actual.num_triangles
+            ));
+        }
+
+        match (self.aabb, actual.aabb) {
+            (Some(expected), Some(found)) => {
+                let [min, max] = expected;
+                let extent = (0..3).map(|i| max[i] - min[i]).fold(0., f64::max);
+                let tolerance = (extent * 1e-5).max(1e-6);
+
+                let matches = expected
+                    .iter()
+                    .flatten()
+                    .zip(found.iter().flatten())
+                    .all(|(a, b)| (a - b).abs() <= tolerance);
+
+                if !matches {
+                    problems.push(format!(
+                        "expected bounding box {expected:?}, found {found:?}"
+                    ));
+                }
+            }
+            (None, None) => {}
+            (expected, found) => {
+                problems.push(format!(
+                    "expected bounding box {expected:?}, found {found:?}"
+                ));
+            }
+        }
+
+        if self.watertight != actual.watertight {
+            problems.push(format!(
+                "expected watertight: {}, found watertight: {}",
+                self.watertight, actual.watertight
+            ));
+        }
+
+        problems
+    }
+}
+
+fn is_watertight(triangles: &[Triangle]) -> bool {
+    // Vertices are welded by their exact position. Files that store points
+    // with lower precision still map identical points to identical points.
+    let mut vertices = HashMap::new();
+    let mut vertex_index = |point: &[f64; 3]| {
+        let key = point.map(|value| {
+            // `0.0` and `-0.0` are the same position.
+            let value = if value == 0. { 0. } else { value };
+            value.to_bits()
+        });
+
+        let next_index = vertices.len();
+        *vertices.entry(key).or_insert(next_index)
+    };
+
+    let mut edges = HashMap::new();
+    for triangle in triangles {
+        let [a, b, c] = [
+            vertex_index(&triangle[0]),
+            vertex_index(&triangle[1]),
+            vertex_index(&triangle[2]),
+        ];
+
+        for edge in [(a, b), (b, c), (c, a)] {
+            *edges.entry(edge).or_insert(0) += 1;
+        }
+    }
+
+    edges.iter().all(|(&(a, b), &count)| {
+        count == 1 && edges.get(&(b, a)).copied() == Some(1)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MeshStats;
+
+    #[test]
+    fn watertight() {
+        let [a, b, c, d] =
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+
+        // A tetrahedron is watertight, but not without one of its faces.
+        let tetrahedron = [[a, c, b], [a, b, d], [b, c, d], [c, a, d]];
+
+        assert!(MeshStats::new(&tetrahedron).watertight);
+        assert!(!MeshStats::new(&tetrahedron[1..]).watertight);
+    }
+
+    #[test]
+    fn compare() {
+        let triangle = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]];
+        let imprecise = [[0., 0., 0.], [1.000_000_1, 0., 0.], [0., 1., 0.]];
+
+        let expected = MeshStats::new(&[triangle]);
+
+        assert!(expected.compare(&MeshStats::new(&[imprecise])).is_empty());
+        assert!(!expected.compare(&MeshStats::new(&[])).is_empty());
+    }
+}